@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Context, Result};
 use derive_getters::Getters;
 use serde_derive::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::path::Path;
 
 #[derive(Deserialize, Debug, Getters)]
@@ -9,6 +11,7 @@ pub struct Config {
     scanner: Scanner,
     decorators: Decorators,
     output: Output,
+    aliases: Option<HashMap<String, String>>,
 }
 
 #[derive(Deserialize, Debug, Getters)]
@@ -18,6 +21,23 @@ pub struct Scanner {
     skip_unknown_files: Option<bool>,
     #[serde(alias = "skip-parsing-errors")]
     skip_parsing_errors: Option<bool>,
+    #[serde(alias = "skip-attribute-conflicts")]
+    skip_attribute_conflicts: Option<bool>,
+    #[serde(alias = "handle-prefixes")]
+    handle_prefixes: Option<Vec<HandlePrefix>>,
+    markers: Option<Markers>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct Markers {
+    names: Option<Vec<String>>,
+    handle: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct HandlePrefix {
+    paths: Vec<String>,
+    prefix: String,
 }
 
 #[derive(Deserialize, Debug, Getters)]
@@ -27,27 +47,126 @@ pub struct LocalScanner {
     exclude: Option<Vec<String>>,
 }
 
-#[derive(Deserialize, Debug, Getters)]
-pub struct Decorators {
-    external_links: Option<LinksDecorator>,
+/*@[CLI/Config]:
+Decorators are configured as an ordered list (`[[decorators]]`) of names and options, and applied in
+the same order. The older table form (`[decorators]` with a key per decorator) is still accepted, its
+decorators are applied in the order they were hard-coded in before: `blame`, `owners`, `external_links`
+(with `include` going first, as the others apply to the included notes too).
+*/
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Decorators {
+    Pipeline(Vec<DecoratorConfig>),
+    Table(Map<String, Value>),
 }
 
 #[derive(Deserialize, Debug, Getters)]
-pub struct LinksDecorator {
-    root: String,
-    format: Option<String>,
+pub struct DecoratorConfig {
+    name: String,
+    #[serde(flatten)]
+    options: Map<String, Value>,
+}
+
+const TABLE_ORDER: [&str; 4] = ["include", "blame", "owners", "external-links"];
+
+impl Decorators {
+    pub fn pipeline(&self) -> Vec<(String, Value)> {
+        match self {
+            Decorators::Pipeline(d) => d
+                .iter()
+                .map(|d| (d.name.clone(), Value::Object(d.options.clone())))
+                .collect(),
+            Decorators::Table(t) => {
+                let mut d: Vec<_> = t.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                d.sort_by_key(|(k, _)| {
+                    TABLE_ORDER
+                        .iter()
+                        .position(|o| *o == k.replace('_', "-"))
+                        .unwrap_or(TABLE_ORDER.len())
+                });
+                d
+            }
+        }
+    }
+}
+
+/*@[CLI/Config]:
+Each output can be configured either as a single table or as an array of tables, so the same kind of
+document can be rendered several times (e.g. for different audiences) without changing the common case.
+*/
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(v) => std::slice::from_ref(v),
+            OneOrMany::Many(v) => v.as_slice(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Getters)]
 pub struct Output {
     root: Option<String>,
-    markdown: MarkdownOutput,
+    markdown: Option<OneOrMany<MarkdownOutput>>,
+    json: Option<OneOrMany<JsonOutput>>,
+    template: Option<OneOrMany<TemplateOutput>>,
+    mdbook: Option<OneOrMany<MdBookOutput>>,
+    graph: Option<OneOrMany<GraphOutput>>,
 }
 
 #[derive(Deserialize, Debug, Getters)]
 pub struct MarkdownOutput {
     path: String,
     toc: bool,
+    quotes: Option<bool>,
+    anchors: Option<String>,
+    blame: Option<bool>,
+    audience: Option<String>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct JsonOutput {
+    path: String,
+    audience: Option<String>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct TemplateOutput {
+    template: String,
+    path: String,
+    audience: Option<String>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct MdBookOutput {
+    path: String,
+    depth: Option<usize>,
+    audience: Option<String>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Getters)]
+pub struct GraphOutput {
+    path: String,
+    format: Option<String>,
+    prefix: Option<String>,
+    depth: Option<usize>,
+    audience: Option<String>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
 }
 
 impl Config {
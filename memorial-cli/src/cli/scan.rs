@@ -1,13 +1,18 @@
 use std::collections::HashMap;
 use std::env;
+use std::fs;
 use std::path::PathBuf;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 
 use memorial_core::api::events::{Event, EventHandler};
 use memorial_core::collector::collector::Collector;
 use memorial_core::collector::file_matcher::FileTypeMatcher;
-use memorial_core::decorators::{links, root, Decorator};
+use memorial_core::collector::markers::MarkerExtractor;
+use memorial_core::decorators::audience::AudienceDecorator;
+use memorial_core::decorators::filter::HandleFilterDecorator;
+use memorial_core::decorators::registry::{DecoratorContext, DecoratorRegistry};
+use memorial_core::decorators::{root, Decorator};
 use memorial_core::model::attributes;
 use memorial_core::model::handle::Handle;
 use memorial_core::parser::csharp::CSharpParser;
@@ -17,8 +22,12 @@ use memorial_core::parser::javascript::JavaScriptParser;
 use memorial_core::parser::kotlin::KotlinParser;
 use memorial_core::parser::protobuf::ProtobufParser;
 use memorial_core::parser::rust::RustParser;
+use memorial_core::renderer::graph::{GraphFormat, GraphRenderer};
+use memorial_core::renderer::json::JsonRenderer;
 use memorial_core::renderer::markdown::MarkdownRenderer;
+use memorial_core::renderer::mdbook::MdBookRenderer;
 use memorial_core::renderer::staging::StagingArea;
+use memorial_core::renderer::template::TemplateRenderer;
 use memorial_core::renderer::Renderer;
 use memorial_core::scanner::local::LocalFileScanner;
 use memorial_core::scanner::FileScanner;
@@ -32,14 +41,21 @@ pub struct ScanAction {
 }
 
 impl ScanAction {
-    fn build_scanner(&self) -> Result<impl FileScanner> {
-        let scanner_config = self.config.scanner().local();
-
-        let root = scanner_config
+    fn scanner_root(&self) -> Result<PathBuf> {
+        Ok(self
+            .config
+            .scanner()
+            .local()
             .root()
             .as_ref()
             .map(|r| PathBuf::from(r))
-            .unwrap_or(env::current_dir()?);
+            .unwrap_or(env::current_dir()?))
+    }
+
+    fn build_scanner(&self) -> Result<impl FileScanner> {
+        let scanner_config = self.config.scanner().local();
+
+        let root = self.scanner_root()?;
 
         Ok(LocalFileScanner::new(
             root,
@@ -83,6 +99,26 @@ impl ScanAction {
             Box::new(RustParser {}),
         );
 
+        for p in self.config.scanner().handle_prefixes().iter().flatten() {
+            collector.register_handle_prefix(p.paths(), Handle::from_str(p.prefix())?)?;
+        }
+
+        if let Some(m) = self.config.scanner().markers() {
+            let names = m.names().clone().unwrap_or(
+                ["TODO", "FIXME", "HACK"]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect(),
+            );
+            let handle = Handle::from_str(m.handle().as_deref().unwrap_or("Tech Debt"))?;
+
+            collector.register_markers(MarkerExtractor::new(&names, handle)?);
+        }
+
+        for (alias, canonical) in self.config.aliases().iter().flatten() {
+            collector.register_alias(Handle::from_str(alias)?, Handle::from_str(canonical)?);
+        }
+
         Ok(collector)
     }
 
@@ -91,15 +127,137 @@ impl ScanAction {
             title: self.config.title().clone(),
         })];
 
-        if let Some(l) = self.config.decorators().external_links() {
-            decorators.push(Box::new(links::LinksDecorator::new(
-                l.root().to_string(),
-                l.format().clone(),
-            )?));
+        let registry = DecoratorRegistry::new();
+        let context = DecoratorContext {
+            root: self.scanner_root()?,
         };
 
+        for (name, options) in self.config.decorators().pipeline() {
+            decorators.push(registry.build(&name, &context, options)?);
+        }
+
         Ok(decorators)
     }
+
+    fn build_targets(&self) -> Result<Vec<Target>> {
+        let output = self.config.output();
+        let mut targets: Vec<Target> = vec![];
+
+        for m in output.markdown().iter().flat_map(|o| o.as_slice()) {
+            targets.push(Target {
+                path: m.path().clone(),
+                audience: m.audience().clone(),
+                filter: Self::handle_filter(m.include(), m.exclude())?,
+                attributes: HashMap::from([
+                    (
+                        attributes::ANCHORS.to_string(),
+                        m.anchors().clone().unwrap_or("handle".to_string()),
+                    ),
+                    (
+                        attributes::OUTPUT_FILE_NAME.to_string(),
+                        m.path().to_string(),
+                    ),
+                    (attributes::TOC.to_string(), m.toc().to_string()),
+                    (
+                        attributes::QUOTE_NOTES.to_string(),
+                        m.quotes().unwrap_or(true).to_string(),
+                    ),
+                    (
+                        attributes::SHOW_BLAME.to_string(),
+                        m.blame().unwrap_or(false).to_string(),
+                    ),
+                ]),
+                renderer: Box::new(MarkdownRenderer::new()),
+            });
+        }
+
+        for j in output.json().iter().flat_map(|o| o.as_slice()) {
+            targets.push(Target {
+                path: j.path().clone(),
+                audience: j.audience().clone(),
+                filter: Self::handle_filter(j.include(), j.exclude())?,
+                attributes: Default::default(),
+                renderer: Box::new(JsonRenderer::new(j.path().clone())),
+            });
+        }
+
+        for t in output.template().iter().flat_map(|o| o.as_slice()) {
+            let template = fs::read_to_string(t.template())
+                .context(format!("Unable to load template from {}", t.template()))?;
+
+            targets.push(Target {
+                path: t.path().clone(),
+                audience: t.audience().clone(),
+                filter: Self::handle_filter(t.include(), t.exclude())?,
+                attributes: Default::default(),
+                renderer: Box::new(TemplateRenderer::new(&template, t.path().clone())?),
+            });
+        }
+
+        for b in output.mdbook().iter().flat_map(|o| o.as_slice()) {
+            targets.push(Target {
+                path: b.path().clone(),
+                audience: b.audience().clone(),
+                filter: Self::handle_filter(b.include(), b.exclude())?,
+                attributes: Default::default(),
+                renderer: Box::new(MdBookRenderer::new(b.path(), *b.depth())),
+            });
+        }
+
+        for g in output.graph().iter().flat_map(|o| o.as_slice()) {
+            let format = match g.format() {
+                Some(f) => f.parse()?,
+                None => GraphFormat::Mermaid,
+            };
+
+            let prefix = match g.prefix() {
+                Some(p) => Some(Handle::from_str(p)?),
+                None => None,
+            };
+
+            targets.push(Target {
+                path: g.path().clone(),
+                audience: g.audience().clone(),
+                filter: Self::handle_filter(g.include(), g.exclude())?,
+                attributes: Default::default(),
+                renderer: Box::new(GraphRenderer::new(g.path(), format, prefix, *g.depth())),
+            });
+        }
+
+        if targets.is_empty() {
+            return Err(anyhow!(
+                "No outputs configured. Check `output` section of the configuration."
+            ));
+        }
+
+        Ok(targets)
+    }
+
+    fn handle_filter(
+        include: &Option<Vec<String>>,
+        exclude: &Option<Vec<String>>,
+    ) -> Result<Option<HandleFilterDecorator>> {
+        if include.is_none() && exclude.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(HandleFilterDecorator::new(
+            include.as_deref().unwrap_or_default(),
+            exclude.as_deref().unwrap_or_default(),
+        )?))
+    }
+}
+
+/*@[CLI/Scan]:
+Output target is a renderer along with the output specific settings: the handles and the audience to filter
+the tree for, and the root attributes (Markdown settings are still passed to the renderer this way).
+*/
+struct Target {
+    path: String,
+    filter: Option<HandleFilterDecorator>,
+    audience: Option<String>,
+    attributes: HashMap<String, String>,
+    renderer: Box<dyn Renderer>,
 }
 
 impl Action for ScanAction {
@@ -110,9 +268,7 @@ impl Action for ScanAction {
 
         let decorators = self.build_decorators()?;
 
-        let mut fs = StagingArea::new();
-
-        let renderer = MarkdownRenderer::new();
+        let targets = self.build_targets()?;
 
         collector.scan(&scanner, self)?;
 
@@ -121,44 +277,10 @@ impl Action for ScanAction {
             return Ok(());
         }
 
-        decorators
-            .iter()
-            .for_each(|d| d.decorate(collector.collected_mut()).unwrap());
-
-        /*@[CLI/Scan]:
-        Even though the overall design and the config model allow for using multiple renderers, this
-        feels like a rabbit hole of over-generalization.
-        So the idea is to keep Markdown as the one and only renderer until the rest of the project is
-        mature enough and there's a clear(er) vision of the roadmap.
-        */
-
-        collector.collected_mut().merge_attributes(
-            &Handle::ROOT,
-            HashMap::from([
-                (
-                    attributes::OUTPUT_FILE_NAME.to_string(),
-                    self.config.output().markdown().path().to_string(),
-                ),
-                (
-                    attributes::TOC.to_string(),
-                    self.config.output().markdown().toc().to_string(),
-                ),
-            ]),
-        );
-
         if self.verbose_mode {
             println!("\nCollected notes:\n{:#?}", collector.collected_mut());
         }
 
-        println!(
-            "\nRendering into {}",
-            self.config.output().markdown().path()
-        );
-
-        renderer.render(collector.collected_mut(), &mut fs)?;
-
-        println!("\nFlushing the files...");
-
         let output_root = self
             .config
             .output()
@@ -167,6 +289,40 @@ impl Action for ScanAction {
             .map(|r| PathBuf::from(r))
             .unwrap_or(env::current_dir()?);
 
+        let mut fs = StagingArea::in_dir(&output_root)?;
+
+        /*@[CLI/Scan]:
+        Each output target gets its own copy of the collected tree, so the output specific settings
+        and filtering (see @[Core/Decorators/Filter] and @[Core/Decorators/Audience]) don't affect the other outputs.
+        Renderers don't modify the tree, so the order they are applied in doesn't matter.
+
+        The decorators run after the filtering, so the derived data (like section numbers or owners)
+        reflects only the nodes and notes making it into the output.
+        */
+        for target in &targets {
+            println!("\nRendering into {}", target.path);
+
+            let mut tree = collector.collected_mut().clone();
+
+            tree.merge_attributes(&Handle::ROOT, target.attributes.clone());
+
+            if let Some(f) = &target.filter {
+                f.decorate(&mut tree)?;
+            }
+
+            if let Some(a) = &target.audience {
+                AudienceDecorator::new(a.clone()).decorate(&mut tree)?;
+            }
+
+            for d in &decorators {
+                d.decorate(&mut tree)?;
+            }
+
+            target.renderer.render(&tree, &mut fs)?;
+        }
+
+        println!("\nFlushing the files...");
+
         fs.flush_to_os_fs(output_root)?;
 
         println!("Done!");
@@ -194,6 +350,40 @@ impl EventHandler for ScanAction {
                     ));
                 }
             }
+            Event::AttributeConflict(c) => {
+                println!(
+                    "Warning: conflicting values of `{}` attribute for [{}]: `{}` at {} (line {}), `{}` at {} (line {})",
+                    c.key,
+                    c.handle,
+                    c.previous.0,
+                    c.previous.1.path(),
+                    c.previous.1.line(),
+                    c.current.0,
+                    c.current.1.path(),
+                    c.current.1.line()
+                );
+                if !self
+                    .config
+                    .scanner()
+                    .skip_attribute_conflicts()
+                    .unwrap_or(true)
+                {
+                    return Err(anyhow!(
+                        "Resolve the conflicting values or set `skip-attribute-conflicts` property to `true`."
+                    ));
+                }
+            }
+            Event::AliasUsed(u) => println!(
+                "Warning: deprecated handle [{}] used at {} (line {}), use [{}] instead",
+                u.alias,
+                u.location.path(),
+                u.location.line(),
+                u.canonical
+            ),
+            Event::CircularAlias(a) => println!(
+                "Warning: circular alias [{}] of [{}] is ignored",
+                a.alias, a.canonical
+            ),
             Event::ParsingFinished(_, notes) if notes > 0 => println!("- Found {} note(s)", notes),
             Event::ParsingFinished(_, _) => {}
             Event::ScanFinished => {}
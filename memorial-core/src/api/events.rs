@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
+use crate::model::file_location::FileLocation;
+use crate::model::handle::Handle;
+
 /*@[Core/API]: Event-based callback system allows to decouple core logic from UI without complicating
 abstractions. Also works really well in unit tests.
 
@@ -14,9 +17,46 @@ pub enum Event {
     ParsingStarted(PathBuf),
     ParsingFailed(PathBuf, String),
     ParsingFinished(PathBuf, usize),
+    AttributeConflict(Box<AttributeConflict>),
+    AliasUsed(Box<AliasUsage>),
+    CircularAlias(Box<CircularAlias>),
     ScanFinished,
 }
 
+/*@[Core/API]:
+Conflicting attribute values are reported with both source locations, as the conflicts usually come from
+different files (or even different repositories) and it's not obvious which one should be fixed.
+The later value still wins, so the result is the same as without the detection.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct AttributeConflict {
+    pub handle: Handle,
+    pub key: String,
+    pub previous: (String, FileLocation),
+    pub current: (String, FileLocation),
+}
+
+/*@[Core/API]:
+Aliases keep the old handles working after renaming a concept, but every usage is reported, so the
+references can be updated eventually.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct AliasUsage {
+    pub alias: Handle,
+    pub canonical: Handle,
+    pub location: FileLocation,
+}
+
+/*@[Core/API]:
+A circular alias (including an alias of a handle to itself or to its own child) can't be resolved.
+It's reported and ignored, so one misplaced `{alias}` doesn't fail the whole scan.
+*/
+#[derive(Debug, Eq, PartialEq)]
+pub struct CircularAlias {
+    pub alias: Handle,
+    pub canonical: Handle,
+}
+
 pub trait EventHandler {
     fn send(&mut self, event: Event) -> Result<()>;
 }
@@ -3,23 +3,41 @@ use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use pest::error::LineColLocation;
 
-use crate::api::events::{Event, EventHandler};
+use crate::api::events::{AliasUsage, AttributeConflict, CircularAlias, Event, EventHandler};
 use crate::collector::file_matcher::FileTypeMatcher;
+use crate::collector::markers::MarkerExtractor;
 use crate::collector::quote_parser::QuoteParser;
 use crate::collector::{quote_parser, QuoteSpan};
 use crate::model::attributes;
+use crate::model::attributes::Scope;
 use crate::model::file_location::FileLocation;
+use crate::model::handle::Handle;
 use crate::model::note::{Note, NoteSpan};
 use crate::model::tree::Node;
 use crate::parser::{FileParser, Quote};
+use crate::scanner::path_filter::PathFilter;
 use crate::scanner::{File, FileScanner};
 
 pub struct Collector {
     collected: Node,
     parsers: HashMap<FileTypeMatcher, Box<dyn FileParser>>,
+    origins: HashMap<(Handle, String), (String, FileLocation)>,
+    aliases: HashMap<Handle, Handle>,
+    pending: Vec<Collected>,
+    prefixes: Vec<(PathFilter, Handle)>,
+    markers: Option<MarkerExtractor>,
+    file_base: Option<Handle>,
+    file_prefix: Option<Handle>,
+}
+
+struct Collected {
+    handle: Handle,
+    note: Option<Note>,
+    attributes: HashMap<String, String>,
+    location: FileLocation,
 }
 
 impl Collector {
@@ -27,6 +45,13 @@ impl Collector {
         Collector {
             collected: Node::root(),
             parsers: Default::default(),
+            origins: Default::default(),
+            aliases: Default::default(),
+            pending: vec![],
+            prefixes: vec![],
+            markers: None,
+            file_base: None,
+            file_prefix: None,
         }
     }
 
@@ -34,6 +59,22 @@ impl Collector {
         self.parsers.insert(matcher, parser);
     }
 
+    pub fn register_alias(&mut self, alias: Handle, canonical: Handle) {
+        self.aliases.insert(alias, canonical);
+    }
+
+    /*@[Core/Collector]:
+    Handle prefixes namespace all the handles (in the headers and the links) found in the files matching
+    the path globs. This allows merging the notes from different teams or repositories without clashes.
+    Relative handles are already resolved against the prefixed ones, and the handles starting with `/`
+    escape the prefix. The first matching prefix wins.
+    */
+    pub fn register_handle_prefix(&mut self, paths: &Vec<String>, prefix: Handle) -> Result<()> {
+        self.prefixes
+            .push((PathFilter::from_glob(paths, &vec![])?, prefix));
+        Ok(())
+    }
+
     pub fn scan<X: File>(
         &mut self,
         scanner: &dyn FileScanner<F = X>,
@@ -63,6 +104,13 @@ impl Collector {
 
             let quotes_len = quotes.len();
 
+            self.file_base = None;
+            self.file_prefix = self
+                .prefixes
+                .iter()
+                .find(|(f, _)| f.is_allowed(path))
+                .map(|(_, p)| p.clone());
+
             let errors: Vec<anyhow::Error> = quotes
                 .into_iter()
                 .filter_map(|q| self.process_quote(q, path.clone()).err())
@@ -85,12 +133,41 @@ impl Collector {
             ))?;
         }
 
+        self.flush(event_handler)?;
+
         event_handler.send(Event::ScanFinished)?;
 
         Ok(())
     }
 
+    pub fn register_markers(&mut self, markers: MarkerExtractor) {
+        self.markers = Some(markers);
+    }
+
     fn process_quote(&mut self, quote: Quote, path: PathBuf) -> Result<()> {
+        if let Some(marker) = match &self.markers {
+            Some(m) => m.extract(&quote.body)?,
+            None => None,
+        } {
+            let location = FileLocation::new_relative(path, quote.line);
+            let note = Note::new(location.clone(), vec![NoteSpan::Text(marker.text)])
+                .with_attributes(marker.attributes)
+                .with_lines(quote.lines);
+
+            /*@[Core/Collector]:
+            Markers are collected under the configured handle as is, without the handle prefix of the file,
+            so all the tech debt ends up in one place.
+            */
+            self.pending.push(Collected {
+                handle: marker.handle.resolve(&Handle::ROOT)?,
+                note: Some(note),
+                attributes: Default::default(),
+                location,
+            });
+
+            return Ok(());
+        }
+
         let mut parts = QuoteParser::parse_from_str(&quote.body)?;
 
         let handle = match parts.remove(0) {
@@ -99,32 +176,263 @@ impl Collector {
         }
         .unwrap(); //todo: handle more gracefully
 
-        let mut attributes: HashMap<String, String> = Default::default();
+        /*@[Core/Collector]:
+        Relative handles in the note headers are resolved against the file base, which is set by a note
+        having `{file-base}` attribute and applies to the rest of the file (the handle prefix of the file
+        is used by default). Relative links in the note body are resolved against the note's own handle instead.
+        */
+        let handle = match (
+            self.file_base.as_ref().or(self.file_prefix.as_ref()),
+            handle.is_relative(),
+        ) {
+            (_, false) => self.apply_prefix(&handle)?,
+            (Some(base), true) => handle.resolve(base)?,
+            (None, true) => {
+                return Err(anyhow!(
+                    "Relative handle `{}` used without a file base",
+                    handle
+                ))
+            }
+        };
+
+        let mut node_attributes: HashMap<String, String> = Default::default();
+        let mut note_attributes: HashMap<String, String> = Default::default();
+        let mut collect = true;
         let mut note_spans: Vec<NoteSpan> = Default::default();
 
         for p in parts {
             match p {
-                QuoteSpan::Attribute(k, v) => {
-                    attributes.insert(k, v);
+                QuoteSpan::Attribute(k, v) => match attributes::scope_of(&k) {
+                    Scope::Node => {
+                        node_attributes.insert(k, v);
+                    }
+                    Scope::Note => {
+                        note_attributes.insert(k, v);
+                    }
+                    Scope::Collection => match k.as_str() {
+                        attributes::DO_NOT_COLLECT => collect = false,
+                        attributes::ALIAS => self.register_alias(
+                            self.apply_prefix(&Handle::from_quote_str(&v)?)?,
+                            handle.clone(),
+                        ),
+                        attributes::FILE_BASE => self.file_base = Some(handle.clone()),
+                        _ => {}
+                    },
+                },
+                QuoteSpan::Link(h) if h.is_relative() => {
+                    note_spans.push(NoteSpan::Link(h.resolve(&handle)?))
                 }
-                QuoteSpan::Link(h) => note_spans.push(NoteSpan::Link(h)),
+                QuoteSpan::Link(h) => note_spans.push(NoteSpan::Link(self.apply_prefix(&h)?)),
                 QuoteSpan::Text(s) => note_spans.push(NoteSpan::Text(s)),
             }
         }
 
-        let note = Note::new(FileLocation::new_relative(path, quote.line), note_spans);
+        let location = FileLocation::new_relative(path, quote.line);
 
-        if attributes.contains_key(attributes::DO_NOT_COLLECT) {
-            attributes.remove(attributes::DO_NOT_COLLECT);
-        } else {
-            self.collected.add_note(&handle, note);
+        /*@[Core/Collector]:
+        Note attributes declared without a note (no body or `{do-not-collect}`) have nothing to be attached to,
+        so they are attached to the node instead. This way, e.g. `{audience:X}` can mark both the notes and the nodes.
+        */
+        if !collect || note_spans.is_empty() {
+            node_attributes.extend(note_attributes.drain());
         }
 
-        self.collected.merge_attributes(&handle, attributes);
+        Self::validate_order(&node_attributes)?;
+
+        let note = match collect {
+            true => {
+                let priority = Self::parse_priority(&note_attributes)?;
+                Some(
+                    Note::new(location.clone(), note_spans)
+                        .with_attributes(note_attributes)
+                        .with_priority(priority)
+                        .with_lines(quote.lines),
+                )
+            }
+            false => None,
+        };
+
+        self.pending.push(Collected {
+            handle,
+            note,
+            attributes: node_attributes,
+            location,
+        });
+
+        Ok(())
+    }
+
+    fn apply_prefix(&self, handle: &Handle) -> Result<Handle> {
+        match &self.file_prefix {
+            Some(p) if !handle.is_rooted() => {
+                let mut parts = p.parts().clone();
+                parts.extend_from_slice(handle.parts());
+                Handle::from_parts(parts)
+            }
+            _ => handle.resolve(&Handle::ROOT),
+        }
+    }
+
+    /*@[Core/Collector]:
+    Aliases can be declared anywhere, including the files scanned after the ones using them, so the
+    collected notes are only added to the tree once all the files are scanned and all the aliases are known.
+    */
+    fn flush(&mut self, event_handler: &mut dyn EventHandler) -> Result<()> {
+        self.drop_circular_aliases(event_handler)?;
+
+        for mut c in std::mem::take(&mut self.pending) {
+            let mut handles = vec![&mut c.handle];
+
+            if let Some(note) = &mut c.note {
+                for s in note.spans_mut() {
+                    if let NoteSpan::Link(h) = s {
+                        handles.push(h);
+                    }
+                }
+            }
+
+            for h in handles {
+                if let Some(canonical) = self.resolve_alias(h)? {
+                    event_handler.send(Event::AliasUsed(Box::new(AliasUsage {
+                        alias: std::mem::replace(h, canonical.clone()),
+                        canonical,
+                        location: c.location.clone(),
+                    })))?;
+                }
+            }
+
+            for conflict in self.track_origins(&c.handle, &c.attributes, &c.location) {
+                event_handler.send(Event::AttributeConflict(Box::new(conflict)))?;
+            }
+
+            if let Some(note) = c.note {
+                self.collected.add_note(&c.handle, note);
+            }
+
+            self.collected.merge_attributes(&c.handle, c.attributes);
+        }
 
         Ok(())
     }
 
+    /*@[Core/Collector]:
+    Circular aliases are dropped one at a time (in the handle order, to be deterministic), as dropping
+    one alias is enough to break a loop and the rest of the aliases in it are still valid.
+    */
+    fn drop_circular_aliases(&mut self, event_handler: &mut dyn EventHandler) -> Result<()> {
+        loop {
+            let mut aliases = self.aliases.keys().cloned().collect::<Vec<_>>();
+            aliases.sort_by_key(|a| a.to_string());
+
+            let circular = match aliases.into_iter().find(|a| self.resolve_alias(a).is_err()) {
+                Some(a) => a,
+                None => return Ok(()),
+            };
+
+            let canonical = self.aliases.remove(&circular).unwrap();
+            event_handler.send(Event::CircularAlias(Box::new(CircularAlias {
+                alias: circular,
+                canonical,
+            })))?;
+        }
+    }
+
+    /*@[Core/Collector]:
+    Aliases redirect the whole subtree: with `Orders` aliased to `Sales`, `Orders/Refunds` becomes
+    `Sales/Refunds`. The longest matching alias wins, and the aliases can be chained (but not looped).
+    */
+    fn resolve_alias(&self, handle: &Handle) -> Result<Option<Handle>> {
+        let mut resolved = handle.clone();
+
+        for _ in 0..=self.aliases.len() {
+            let found = self
+                .aliases
+                .iter()
+                .filter(|(a, _)| resolved.parts().starts_with(a.parts()))
+                .max_by_key(|(a, _)| a.parts().len());
+
+            match found {
+                Some((a, c)) => {
+                    let mut parts = c.parts().clone();
+                    parts.extend_from_slice(&resolved.parts()[a.parts().len()..]);
+                    resolved = Handle::from_parts(parts)?;
+                }
+                None if resolved == *handle => return Ok(None),
+                None => return Ok(Some(resolved)),
+            }
+        }
+
+        Err(anyhow!("Circular alias found for `{}`", handle))
+    }
+
+    /*@[Core/Collector]:
+    Node attributes can be declared in any number of places, so the collector remembers where each value
+    came from to be able to report the conflicting declarations. Repeating the same value is fine.
+    */
+    fn track_origins(
+        &mut self,
+        handle: &Handle,
+        attributes: &HashMap<String, String>,
+        location: &FileLocation,
+    ) -> Vec<AttributeConflict> {
+        let mut conflicts = vec![];
+
+        for (k, v) in attributes {
+            let current = (v.clone(), location.clone());
+
+            if let Some(previous) = self
+                .origins
+                .insert((handle.clone(), k.clone()), current.clone())
+            {
+                if previous.0 != current.0 {
+                    conflicts.push(AttributeConflict {
+                        handle: handle.clone(),
+                        key: k.clone(),
+                        previous,
+                        current,
+                    });
+                }
+            }
+        }
+
+        conflicts.sort_by(|a, b| a.key.cmp(&b.key));
+        conflicts
+    }
+
+    /*@[Core/Collector]:
+    Priority (or its alias, weight) is validated while collecting, so a typo fails the scan instead of
+    silently leaving the note unpinned. A value is optional, `{priority}` alone is the same as `{priority:1}`.
+    */
+    /*@[Core/Collector]:
+    Order values are only used when rendering, so an invalid one would be silently ignored there, quietly
+    reordering the sections. It's rejected when collected instead, same as an invalid priority.
+    */
+    fn validate_order(attributes: &HashMap<String, String>) -> Result<()> {
+        match attributes.get(attributes::ORDER) {
+            Some(v) if v.trim().parse::<i64>().is_err() => {
+                Err(anyhow!("Invalid {} value `{}`", attributes::ORDER, v))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_priority(attributes: &HashMap<String, String>) -> Result<i64> {
+        let mut priority = 0;
+
+        for key in [attributes::PRIORITY, attributes::WEIGHT] {
+            if let Some(v) = attributes.get(key) {
+                priority = match v.trim() {
+                    "" => 1,
+                    v => v
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid {} value `{}`", key, v))?,
+                };
+            }
+        }
+
+        Ok(priority)
+    }
+
     pub fn collected_mut(&mut self) -> &mut Node {
         &mut self.collected
     }
@@ -141,7 +449,6 @@ impl Collector {
 #[cfg(test)]
 mod test {
     use crate::api::events::StubEventHandler;
-    use crate::model::handle::Handle;
     use crate::parser::go::GoParser;
 
     use super::*;
@@ -183,7 +490,8 @@ mod test {
                 },
                 StubFile {
                     path: "path/to/file2.go".into(),
-                    contents: "//@[a/b/c]{toggle}: note 2, see @[x/y/z] for more".to_string(),
+                    contents: "//@[a/b/c]{toggle}{since:2.3}: note 2, see @[x/y/z] for more"
+                        .to_string(),
                 },
             ],
         };
@@ -227,11 +535,15 @@ mod test {
                 Note::new(
                     FileLocation::new_relative("path/to/file2.go", 1),
                     vec!(
-                        NoteSpan::Text("note 2, see".to_string()),
+                        NoteSpan::Text("note 2, see ".to_string()),
                         NoteSpan::Link(Handle::from_str("x/y/z").unwrap()),
-                        NoteSpan::Text("for more".to_string()),
+                        NoteSpan::Text(" for more".to_string()),
                     ),
-                ),
+                )
+                .with_attributes(HashMap::from([(
+                    attributes::SINCE.to_string(),
+                    "2.3".to_string()
+                )])),
             ),
             *node.notes()
         );
@@ -242,6 +554,387 @@ mod test {
         )
     }
 
+    #[test]
+    fn publishes_events_for_attribute_conflicts() {
+        let scanner = StubScanner {
+            files: vec![
+                StubFile {
+                    path: "path/to/file1.go".into(),
+                    contents: "//@[Billing]{title:Billing}{order:1}".to_string(),
+                },
+                StubFile {
+                    path: "path/to/file2.go".into(),
+                    contents: "//@[Billing]{title:Payments}{order:1}".to_string(),
+                },
+            ],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        let conflicts: Vec<&Event> = event_handler
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::AttributeConflict(_)))
+            .collect();
+
+        assert_eq!(
+            vec!(&Event::AttributeConflict(Box::new(AttributeConflict {
+                handle: Handle::from_str("Billing").unwrap(),
+                key: attributes::TITLE.to_string(),
+                previous: (
+                    "Billing".to_string(),
+                    FileLocation::new_relative("path/to/file1.go", 1)
+                ),
+                current: (
+                    "Payments".to_string(),
+                    FileLocation::new_relative("path/to/file2.go", 1)
+                ),
+            }))),
+            conflicts
+        );
+
+        assert_eq!(
+            Some(&"Payments".to_string()),
+            collector
+                .collected
+                .find_node(&Handle::from_str("Billing").unwrap())
+                .unwrap()
+                .attributes()
+                .get(attributes::TITLE)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_order() {
+        let scanner = StubScanner {
+            files: vec![StubFile {
+                path: "path/to/file1.go".into(),
+                contents: "//@[Billing]{order:first}: billing\n//@[Shipping]{order: 2}: shipping"
+                    .to_string(),
+            }],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        assert!(event_handler.events.contains(&Event::ParsingFailed(
+            "path/to/file1.go".into(),
+            "Invalid order value `first`".to_string()
+        )));
+
+        assert!(collector
+            .collected
+            .find_node(&Handle::from_str("Billing").unwrap())
+            .is_none());
+        assert!(collector
+            .collected
+            .find_node(&Handle::from_str("Shipping").unwrap())
+            .is_some());
+    }
+
+    #[test]
+    fn resolves_relative_handles() {
+        let scanner = StubScanner {
+            files: vec![
+                StubFile {
+                    path: "path/to/file1.go".into(),
+                    contents: "//@[Billing/Invoices]{file-base}\npackage billing\n//@[./Rounding]: see @[../Taxes]".to_string(),
+                },
+                StubFile {
+                    path: "path/to/file2.go".into(),
+                    contents: "//@[./Rounding]: no file base here".to_string(),
+                },
+            ],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        assert!(event_handler.events.contains(&Event::ParsingFailed(
+            "path/to/file2.go".into(),
+            "Relative handle `. / Rounding` used without a file base".to_string()
+        )));
+
+        let node = collector
+            .collected
+            .find_node(&Handle::from_str("Billing/Invoices/Rounding").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            vec!(&vec!(
+                NoteSpan::Text("see ".to_string()),
+                NoteSpan::Link(Handle::from_str("Billing/Invoices/Taxes").unwrap())
+            )),
+            node.notes().iter().map(|n| n.spans()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn collects_markers_without_prefixes() {
+        let scanner = StubScanner {
+            files: vec![StubFile {
+                path: "services/billing/invoices.go".into(),
+                contents: "// TODO(alice, PROJ-12): fix rounding".to_string(),
+            }],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector
+            .register_handle_prefix(
+                &vec!["services/billing/**".to_string()],
+                Handle::from_str("Billing").unwrap(),
+            )
+            .unwrap();
+
+        collector.register_markers(
+            MarkerExtractor::new(
+                &["TODO".to_string()],
+                Handle::from_str("Tech Debt").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        let node = collector
+            .collected
+            .find_node(&Handle::from_str("Tech Debt/TODO").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            &vec!(NoteSpan::Text("fix rounding".to_string())),
+            node.notes()[0].spans()
+        );
+        assert!(collector
+            .collected
+            .find_node(&Handle::from_str("Billing").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn applies_handle_prefixes() {
+        let scanner = StubScanner {
+            files: vec![
+                StubFile {
+                    path: "services/billing/invoices.go".into(),
+                    contents: "//@[Invoices]: see @[Taxes] and @[/Shared/Money]".to_string(),
+                },
+                StubFile {
+                    path: "services/shared/money.go".into(),
+                    contents: "//@[Shared/Money]: money".to_string(),
+                },
+            ],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector
+            .register_handle_prefix(
+                &vec!["services/billing/**".to_string()],
+                Handle::from_str("Billing").unwrap(),
+            )
+            .unwrap();
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        let node = collector
+            .collected
+            .find_node(&Handle::from_str("Billing/Invoices").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            vec!(&vec!(
+                NoteSpan::Text("see ".to_string()),
+                NoteSpan::Link(Handle::from_str("Billing/Taxes").unwrap()),
+                NoteSpan::Text(" and ".to_string()),
+                NoteSpan::Link(Handle::from_str("Shared/Money").unwrap())
+            )),
+            node.notes().iter().map(|n| n.spans()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            1,
+            collector
+                .collected
+                .find_node(&Handle::from_str("Shared/Money").unwrap())
+                .unwrap()
+                .notes()
+                .len()
+        );
+    }
+
+    #[test]
+    fn redirects_aliased_handles() {
+        let scanner = StubScanner {
+            files: vec![
+                StubFile {
+                    path: "path/to/file1.go".into(),
+                    contents: "//@[Orders/Refunds/Policy]: see @[Orders/Refunds]".to_string(),
+                },
+                StubFile {
+                    path: "path/to/file2.go".into(),
+                    contents: "//@[Payments/Refunds]{alias:Orders/Refunds}".to_string(),
+                },
+            ],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        let usages: Vec<&Event> = event_handler
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::AliasUsed(_)))
+            .collect();
+
+        let usage = |alias: &str, canonical: &str| {
+            Event::AliasUsed(Box::new(AliasUsage {
+                alias: Handle::from_str(alias).unwrap(),
+                canonical: Handle::from_str(canonical).unwrap(),
+                location: FileLocation::new_relative("path/to/file1.go", 1),
+            }))
+        };
+
+        assert_eq!(
+            vec!(
+                &usage("Orders/Refunds/Policy", "Payments/Refunds/Policy"),
+                &usage("Orders/Refunds", "Payments/Refunds"),
+            ),
+            usages
+        );
+
+        assert!(collector
+            .collected
+            .find_node(&Handle::from_str("Orders").unwrap())
+            .is_none());
+
+        let node = collector
+            .collected
+            .find_node(&Handle::from_str("Payments/Refunds/Policy").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            vec!(&vec!(
+                NoteSpan::Text("see ".to_string()),
+                NoteSpan::Link(Handle::from_str("Payments/Refunds").unwrap())
+            )),
+            node.notes().iter().map(|n| n.spans()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn skips_circular_aliases() {
+        let scanner = StubScanner {
+            files: vec![StubFile {
+                path: "path/to/file1.go".into(),
+                contents:
+                    "//@[Orders]{alias:Orders}: orders\n//@[Sales]{alias:Deals}: see @[Deals]"
+                        .to_string(),
+            }],
+        };
+
+        let mut event_handler = StubEventHandler::new();
+        let mut collector = Collector::new();
+
+        collector.register_parser(
+            FileTypeMatcher::Extension("go".to_string()),
+            Box::new(GoParser {}),
+        );
+
+        collector.scan(&scanner, &mut event_handler).unwrap();
+
+        let circular: Vec<&Event> = event_handler
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::CircularAlias(_)))
+            .collect();
+
+        assert_eq!(
+            vec!(&Event::CircularAlias(Box::new(CircularAlias {
+                alias: Handle::from_str("Orders").unwrap(),
+                canonical: Handle::from_str("Orders").unwrap(),
+            }))),
+            circular
+        );
+
+        assert!(collector
+            .collected
+            .find_node(&Handle::from_str("Orders").unwrap())
+            .is_some());
+        assert_eq!(
+            &vec!(
+                NoteSpan::Text("see ".to_string()),
+                NoteSpan::Link(Handle::from_str("Sales").unwrap())
+            ),
+            collector
+                .collected
+                .find_node(&Handle::from_str("Sales").unwrap())
+                .unwrap()
+                .notes()[0]
+                .spans()
+        );
+    }
+
+    #[test]
+    fn fails_on_circular_aliases() {
+        let mut collector = Collector::new();
+
+        collector.register_alias(
+            Handle::from_str("a").unwrap(),
+            Handle::from_str("b").unwrap(),
+        );
+        collector.register_alias(
+            Handle::from_str("b").unwrap(),
+            Handle::from_str("a").unwrap(),
+        );
+
+        assert!(collector
+            .resolve_alias(&Handle::from_str("a/c").unwrap())
+            .is_err());
+    }
+
     #[derive(Clone)]
     struct StubFile {
         path: PathBuf,
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::model::attributes;
+use crate::model::handle::Handle;
+
+/*@[Core/Collector/Markers]:
+Marker extractor turns the comments starting with one of the configured markers (like `TODO` or `FIXME`)
+into notes, so the tech debt becomes part of the document. The notes are placed under `{handle}/{marker}`.
+
+Markers can be followed by the details in parentheses, e.g. `TODO(alice, PROJ-12)`. The details looking
+like issue tracker tickets (`PROJ-12`) become the `ticket` attribute of the note, the rest become the `owner`.
+The marker itself is dropped from the note text (it's already in the handle), along with the details.
+*/
+pub struct MarkerExtractor {
+    pattern: Regex,
+    ticket: Regex,
+    handle: Handle,
+}
+
+pub struct Marker {
+    pub handle: Handle,
+    pub attributes: HashMap<String, String>,
+    pub text: String,
+}
+
+impl MarkerExtractor {
+    pub fn new(markers: &[String], handle: Handle) -> Result<MarkerExtractor> {
+        if markers.is_empty() {
+            return Err(anyhow!("No markers configured"));
+        }
+
+        if markers.iter().any(|m| m.trim().is_empty()) {
+            return Err(anyhow!("Marker names can't be blank"));
+        }
+
+        let names = markers
+            .iter()
+            .map(|m| regex::escape(m.trim()))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        Ok(MarkerExtractor {
+            pattern: Regex::new(&format!(r"^({})\b(?:\(([^)]*)\))?", names))?,
+            ticket: Regex::new(r"^[A-Z][A-Z0-9]*-\d+$")?,
+            handle,
+        })
+    }
+
+    pub fn extract(&self, body: &str) -> Result<Option<Marker>> {
+        let captures = match self.pattern.captures(body) {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let mut owners = vec![];
+        let mut tickets = vec![];
+
+        for d in captures
+            .get(2)
+            .map(|d| d.as_str())
+            .unwrap_or("")
+            .split(',')
+            .map(|d| d.trim())
+            .filter(|d| !d.is_empty())
+        {
+            if self.ticket.is_match(d) {
+                tickets.push(d);
+            } else {
+                owners.push(d);
+            }
+        }
+
+        let mut attributes = HashMap::new();
+
+        if !owners.is_empty() {
+            attributes.insert(attributes::OWNER.to_string(), owners.join(", "));
+        }
+
+        if !tickets.is_empty() {
+            attributes.insert(attributes::TICKET.to_string(), tickets.join(", "));
+        }
+
+        let text = body[captures[0].len()..]
+            .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+            .to_string();
+
+        Ok(Some(Marker {
+            handle: self.handle.join(captures[1].to_string())?,
+            attributes,
+            text: if text.is_empty() {
+                body.to_string()
+            } else {
+                text
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extract_markers() {
+        let extractor = MarkerExtractor::new(
+            &["TODO".to_string(), "FIXME".to_string()],
+            Handle::from_str("Tech Debt").unwrap(),
+        )
+        .unwrap();
+
+        let marker = extractor
+            .extract("TODO(alice, PROJ-12): fix rounding")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(Handle::from_str("Tech Debt/TODO").unwrap(), marker.handle);
+        assert_eq!("fix rounding", marker.text);
+        assert_eq!(
+            HashMap::from([
+                (attributes::OWNER.to_string(), "alice".to_string()),
+                (attributes::TICKET.to_string(), "PROJ-12".to_string()),
+            ]),
+            marker.attributes
+        );
+
+        let marker = extractor.extract("FIXME: leaks").unwrap().unwrap();
+        assert_eq!(Handle::from_str("Tech Debt/FIXME").unwrap(), marker.handle);
+        assert_eq!("leaks", marker.text);
+        assert!(marker.attributes.is_empty());
+
+        let marker = extractor.extract("TODO").unwrap().unwrap();
+        assert_eq!("TODO", marker.text);
+
+        assert!(extractor.extract("TODOS are fine").unwrap().is_none());
+        assert!(extractor.extract("Not a TODO").unwrap().is_none());
+        assert!(extractor.extract("HACK: not configured").unwrap().is_none());
+    }
+
+    #[test]
+    fn reject_empty_markers() {
+        let handle = Handle::from_str("Tech Debt").unwrap();
+
+        assert!(MarkerExtractor::new(&[], handle.clone()).is_err());
+        assert!(MarkerExtractor::new(&["TODO".to_string(), " ".to_string()], handle).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use crate::model::handle::Handle;
 
 pub mod collector;
 pub mod file_matcher;
+pub mod markers;
 mod quote_parser;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
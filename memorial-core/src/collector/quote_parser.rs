@@ -14,8 +14,14 @@ impl QuoteParser {
         let parsed = QuoteParser::parse(Rule::root, source)?;
         let spans: Result<Vec<QuoteSpan>> = parsed
             .into_iter()
+            .flat_map(|p| match p.as_rule() {
+                Rule::body => p.into_inner().collect(),
+                _ => vec![p],
+            })
             .map(|p| match p.as_rule() {
-                Rule::handle => Ok(QuoteSpan::Link(Handle::from_str(p.into_inner().as_str())?)),
+                Rule::handle => Ok(QuoteSpan::Link(Handle::from_quote_str(
+                    p.into_inner().as_str(),
+                )?)),
                 Rule::attr => {
                     let mut inner = p.into_inner();
                     Ok(QuoteSpan::Attribute(
@@ -63,15 +69,30 @@ mod tests {
             )).unwrap()),
             QuoteSpan::Attribute(attributes::TITLE.to_string(), "Domain rules".to_string()),
             QuoteSpan::Attribute("toggle".to_string(), "".to_string()),
-            QuoteSpan::Text("The accumulated value is always increasing when collecting new values.\n            See".to_string()),
+            QuoteSpan::Text("The accumulated value is always increasing when collecting new values.\n            See ".to_string()),
             QuoteSpan::Link(Handle::from_parts(vec!(
                 "Domain".to_string(),
                 "Other".to_string(),
                 "Rule".to_string(),
             )).unwrap()),
-            QuoteSpan::Text("for more details.".to_string()),
+            QuoteSpan::Text(" for more details.\n       ".to_string()),
         );
 
         assert_eq!(expected, parsed)
     }
+
+    #[test]
+    fn parse_quote_preserving_whitespace() {
+        let parsed =
+            QuoteParser::parse_from_str("@[A]:  Hard break  \nthen @[A/B]. Done\t").unwrap();
+
+        let expected = vec![
+            QuoteSpan::Link(Handle::from_str("A").unwrap()),
+            QuoteSpan::Text("Hard break  \nthen ".to_string()),
+            QuoteSpan::Link(Handle::from_str("A/B").unwrap()),
+            QuoteSpan::Text(". Done\t".to_string()),
+        ];
+
+        assert_eq!(expected, parsed)
+    }
 }
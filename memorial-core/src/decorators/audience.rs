@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::decorators::Decorator;
+use crate::model::attributes;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Audience]:
+Audience decorator prunes the tree, leaving only the nodes and the notes meant for the specific audience.
+The audience is declared with `{audience:X}` attribute, which accepts a comma-separated list of values.
+Nodes and notes without the attribute are meant for everyone. Hiding a node hides its whole subtree.
+
+Unlike the other decorators, this one is configured per output and applied before the others,
+so different outputs can target different audiences within one scan.
+*/
+pub struct AudienceDecorator {
+    audience: String,
+}
+
+impl AudienceDecorator {
+    pub fn new(audience: String) -> AudienceDecorator {
+        AudienceDecorator {
+            audience: audience.trim().to_string(),
+        }
+    }
+
+    fn is_visible(&self, attributes: &HashMap<String, String>) -> bool {
+        match attributes.get(attributes::AUDIENCE) {
+            Some(v) => {
+                let audiences: Vec<&str> = v
+                    .split(',')
+                    .map(|a| a.trim())
+                    .filter(|a| !a.is_empty())
+                    .collect();
+                audiences.is_empty() || audiences.contains(&self.audience.as_str())
+            }
+            None => true,
+        }
+    }
+}
+
+impl Decorator for AudienceDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        tree.prune(
+            &|node: &Node| self.is_visible(node.attributes()),
+            &|_, note| self.is_visible(note.attributes()),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::handle::Handle;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn audience(value: &str) -> HashMap<String, String> {
+        HashMap::from([(attributes::AUDIENCE.to_string(), value.to_string())])
+    }
+
+    #[test]
+    fn prune_by_audience() {
+        let mut tree = Node::root();
+
+        let public = Handle::from_str("Public").unwrap();
+        let internal = Handle::from_str("Internal").unwrap();
+
+        tree.add_note(
+            &public,
+            Note::new(
+                FileLocation::new_relative("file1.go", 1),
+                vec![
+                    NoteSpan::Text("see ".to_string()),
+                    NoteSpan::Link(internal.clone()),
+                ],
+            ),
+        );
+
+        tree.add_note(
+            &public,
+            Note::new(
+                FileLocation::new_relative("file1.go", 2),
+                vec![NoteSpan::Text("for ops".to_string())],
+            )
+            .with_attributes(audience("ops, internal")),
+        );
+
+        tree.add_note(
+            &internal,
+            Note::new(
+                FileLocation::new_relative("file2.go", 1),
+                vec![NoteSpan::Link(public.clone())],
+            ),
+        );
+
+        tree.merge_attributes(&public, audience("public,internal"));
+        tree.merge_attributes(&internal, audience("internal"));
+        tree.merge_attributes(
+            &internal,
+            HashMap::from([(attributes::TITLE.to_string(), "Internals".to_string())]),
+        );
+
+        let mut public_tree = tree.clone();
+        AudienceDecorator::new("public".to_string())
+            .decorate(&mut public_tree)
+            .unwrap();
+
+        assert!(public_tree.find_node(&internal).is_none());
+
+        let node = public_tree.find_node(&public).unwrap();
+        assert_eq!(
+            vec!(&vec!(
+                NoteSpan::Text("see ".to_string()),
+                NoteSpan::Text("Internals".to_string())
+            )),
+            node.notes().iter().map(|n| n.spans()).collect::<Vec<_>>()
+        );
+        assert!(node.mentions().is_empty());
+
+        let mut internal_tree = tree.clone();
+        AudienceDecorator::new("internal".to_string())
+            .decorate(&mut internal_tree)
+            .unwrap();
+
+        let node = internal_tree.find_node(&public).unwrap();
+        assert_eq!(2, node.notes().len());
+        assert_eq!(vec!(&internal), node.mentions().iter().collect::<Vec<_>>());
+        assert_eq!(
+            vec!(&public),
+            internal_tree
+                .find_node(&internal)
+                .unwrap()
+                .mentions()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+    }
+}
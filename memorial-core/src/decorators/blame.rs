@@ -0,0 +1,201 @@
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use time::{Duration, OffsetDateTime};
+
+use crate::decorators::Decorator;
+use crate::git::{BlameLine, Repository};
+use crate::model::attributes;
+use crate::model::file_location::FilePath;
+use crate::model::note::Note;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Blame]:
+Blame decorator attaches the last author and the date of the last change to each note, using the local
+Git history of the lines the note spans. Notes not changed for longer than the threshold are marked as
+stale, as these are likely to be outdated.
+
+The decorator has to run before the links decorator, as it needs the relative paths of the files.
+Files not tracked by Git (or a missing Git) simply leave the notes without the metadata, and without
+a repository at all the decorator is not used (see @[Core/Decorators/Registry]).
+The results are cached, as the decorator is applied to each output separately.
+*/
+pub struct BlameDecorator {
+    repository: Repository,
+    stale_after_months: Option<u32>,
+    blamed: RefCell<HashMap<PathBuf, Option<Vec<BlameLine>>>>,
+}
+
+const DAYS_IN_MONTH: i64 = 30;
+
+impl BlameDecorator {
+    pub fn new(repository: Repository, stale_after_months: Option<u32>) -> BlameDecorator {
+        BlameDecorator {
+            repository,
+            stale_after_months,
+            blamed: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn relative_path(note: &Note) -> Option<PathBuf> {
+        match note.location().path() {
+            FilePath::Relative(p) => Some(p.clone()),
+            _ => None,
+        }
+    }
+
+    fn annotate(
+        note: &mut Note,
+        lines: &[BlameLine],
+        now: OffsetDateTime,
+        stale_after_months: Option<u32>,
+    ) -> Result<()> {
+        let first = note.location().line().max(1) - 1;
+
+        let last = match lines
+            .iter()
+            .skip(first)
+            .take(note.lines())
+            .max_by_key(|l| l.time)
+        {
+            Some(l) => l,
+            None => return Ok(()),
+        };
+
+        let modified = OffsetDateTime::from_unix_timestamp(last.time)?;
+
+        let attributes = note.attributes_mut();
+        attributes.insert(attributes::AUTHOR.to_string(), last.author.clone());
+        attributes.insert(
+            attributes::MODIFIED.to_string(),
+            modified.format(&time::format_description::parse("[year]-[month]-[day]")?)?,
+        );
+
+        if let Some(months) = stale_after_months {
+            if now - modified > Duration::days(DAYS_IN_MONTH * months as i64) {
+                attributes.insert(attributes::STALE.to_string(), "true".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Decorator for BlameDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        let paths = RefCell::new(HashSet::new());
+
+        tree.visit(&|node: &Node| {
+            paths
+                .borrow_mut()
+                .extend(node.notes().iter().filter_map(Self::relative_path));
+            Ok(())
+        })?;
+
+        let mut blamed = self.blamed.borrow_mut();
+        for p in paths.into_inner() {
+            if let Entry::Vacant(e) = blamed.entry(p) {
+                let lines = self.repository.blame(e.key()).ok();
+                e.insert(lines);
+            }
+        }
+
+        let now = OffsetDateTime::from(SystemTime::now());
+
+        tree.visit_mut(&|node: &mut Node| {
+            for n in node.notes_mut() {
+                if let Some(Some(lines)) = Self::relative_path(n).and_then(|p| blamed.get(&p)) {
+                    Self::annotate(n, lines, now, self.stale_after_months)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::NoteSpan;
+
+    use super::*;
+
+    #[test]
+    fn annotate_notes() {
+        let lines = vec![
+            BlameLine {
+                author: "Alice".to_string(),
+                time: 1690000000,
+            },
+            BlameLine {
+                author: "Bob".to_string(),
+                time: 1700000000,
+            },
+            BlameLine {
+                author: "Carol".to_string(),
+                time: 1710000000,
+            },
+        ];
+
+        let mut note = Note::new(
+            FileLocation::new_relative("src/lib.rs", 1),
+            vec![NoteSpan::Text("first\nsecond".to_string())],
+        )
+        .with_lines(2);
+
+        let now = OffsetDateTime::from_unix_timestamp(1700000000).unwrap() + Duration::days(30);
+        BlameDecorator::annotate(&mut note, &lines, now, Some(6)).unwrap();
+
+        assert_eq!(
+            HashMap::from([
+                (attributes::AUTHOR.to_string(), "Bob".to_string()),
+                (attributes::MODIFIED.to_string(), "2023-11-14".to_string()),
+            ]),
+            *note.attributes()
+        );
+
+        let now = now + Duration::days(365);
+        BlameDecorator::annotate(&mut note, &lines, now, Some(6)).unwrap();
+
+        assert_eq!(
+            Some(&"true".to_string()),
+            note.attributes().get(attributes::STALE)
+        );
+    }
+
+    #[test]
+    fn annotate_block_comments() {
+        let lines = vec![
+            BlameLine {
+                author: "Alice".to_string(),
+                time: 1690000000,
+            },
+            BlameLine {
+                author: "Bob".to_string(),
+                time: 1700000000,
+            },
+            BlameLine {
+                author: "Carol".to_string(),
+                time: 1710000000,
+            },
+        ];
+
+        // the body starts on the line after the opening marker
+        let mut note = Note::new(
+            FileLocation::new_relative("src/lib.rs", 1),
+            vec![NoteSpan::Text("first\nsecond".to_string())],
+        )
+        .with_lines(3);
+
+        BlameDecorator::annotate(&mut note, &lines, OffsetDateTime::now_utc(), None).unwrap();
+
+        assert_eq!(
+            Some(&"Carol".to_string()),
+            note.attributes().get(attributes::AUTHOR)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Result};
+use glob::{MatchOptions, Pattern};
+
+use crate::decorators::Decorator;
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Filter]:
+Handle filter prunes the tree, leaving only the parts selected by the handle globs, e.g. to publish only
+the `Public API` subtree or to hide the `Internal/Experiments` one. The globs are matched against the handles
+with `/` separating the parts: `*` matches within a single part, `**` matches any number of parts.
+
+A node is included along with its subtree when it or any of its ancestors matches an include glob
+(all nodes are included if there are none). A glob ending with `**` part also matches the node above it.
+The ancestors of the included nodes are kept to hold the structure, but without their own notes.
+Excluded nodes are removed with their subtrees. Links to the removed nodes are turned into text
+and their mentions are dropped (see @[Core/Model/Tree]).
+*/
+pub struct HandleFilterDecorator {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl HandleFilterDecorator {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<HandleFilterDecorator> {
+        Ok(HandleFilterDecorator {
+            include: Self::compile(include)?,
+            exclude: Self::compile(exclude)?,
+        })
+    }
+
+    fn compile(globs: &[String]) -> Result<Vec<Pattern>> {
+        let mut patterns = vec![];
+
+        for g in globs {
+            let g = g.trim().trim_matches('/');
+            let compile =
+                |p: &str| Pattern::new(p).map_err(|e| anyhow!("Invalid handle glob {}: {}", g, e));
+
+            patterns.push(compile(g)?);
+
+            if let Some(base) = g.strip_suffix("/**") {
+                patterns.push(compile(base)?);
+            }
+        }
+
+        Ok(patterns)
+    }
+
+    fn matches(patterns: &[Pattern], handle: &Handle) -> bool {
+        let path = handle.parts().join("/");
+        patterns
+            .iter()
+            .any(|p| p.matches_with(&path, MATCH_OPTIONS))
+    }
+
+    fn is_selected(&self, handle: &Handle) -> bool {
+        self.include.is_empty()
+            || (1..=handle.parts().len()).any(|i| {
+                Handle::from_parts(handle.parts()[..i].to_vec())
+                    .map(|h| Self::matches(&self.include, &h))
+                    .unwrap_or(false)
+            })
+    }
+
+    fn is_visible(&self, node: &Node) -> bool {
+        !Self::matches(&self.exclude, node.handle())
+            && (self.is_selected(node.handle())
+                || node.children().values().any(|n| self.is_visible(n)))
+    }
+}
+
+impl Decorator for HandleFilterDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        tree.prune(&|node: &Node| self.is_visible(node), &|node: &Node, _| {
+            self.is_selected(node.handle())
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn build_tree() -> Node {
+        let mut tree = Node::root();
+
+        for (h, link) in [
+            ("Docs", None),
+            ("Docs/Public API", Some("Internal/Experiments/Flags")),
+            ("Docs/Public API/Orders", None),
+            ("Internal/Experiments/Flags", Some("Docs/Public API")),
+            ("Internal/Storage", None),
+        ] {
+            let mut spans = vec![NoteSpan::Text("note".to_string())];
+            if let Some(l) = link {
+                spans.push(NoteSpan::Link(Handle::from_str(l).unwrap()));
+            }
+
+            tree.add_note(
+                &Handle::from_str(h).unwrap(),
+                Note::new(FileLocation::new_relative("src/lib.rs", 1), spans),
+            );
+        }
+
+        tree
+    }
+
+    fn find<'a>(tree: &'a Node, handle: &str) -> Option<&'a Node> {
+        tree.find_node(&Handle::from_str(handle).unwrap())
+    }
+
+    #[test]
+    fn include_handles() {
+        let mut tree = build_tree();
+
+        HandleFilterDecorator::new(&["Docs/Public API/**".to_string()], &[])
+            .unwrap()
+            .decorate(&mut tree)
+            .unwrap();
+
+        assert!(find(&tree, "Docs").unwrap().notes().is_empty());
+        assert_eq!(
+            1,
+            find(&tree, "Docs/Public API/Orders").unwrap().notes().len()
+        );
+        assert!(find(&tree, "Internal").is_none());
+
+        let public = find(&tree, "Docs/Public API").unwrap();
+        assert_eq!(
+            &vec![
+                NoteSpan::Text("note".to_string()),
+                NoteSpan::Text("Flags".to_string()),
+            ],
+            public.notes()[0].spans()
+        );
+        assert!(public.mentions().is_empty());
+    }
+
+    #[test]
+    fn exclude_handles() {
+        let mut tree = build_tree();
+
+        HandleFilterDecorator::new(&[], &["*/Experiments/**".to_string()])
+            .unwrap()
+            .decorate(&mut tree)
+            .unwrap();
+
+        assert!(find(&tree, "Internal/Experiments").is_none());
+        assert!(find(&tree, "Internal/Storage").is_some());
+        assert_eq!(1, find(&tree, "Docs").unwrap().notes().len());
+        assert!(find(&tree, "Docs/Public API")
+            .unwrap()
+            .mentions()
+            .is_empty());
+    }
+}
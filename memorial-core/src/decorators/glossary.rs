@@ -0,0 +1,364 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::decorators::Decorator;
+use crate::model::attributes;
+use crate::model::handle::Handle;
+use crate::model::note::NoteSpan;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Glossary]:
+Glossary decorator links the plain text mentions of the glossary terms to their definitions, so the authors
+don't have to write the links explicitly. The terms are the titles of the nodes with notes (definitions) under the
+glossary handle (`Glossary` by default), matched as whole words, case-insensitively. Longer terms take precedence, so
+"Settlement Window" wins over "Settlement".
+
+Only the first mention of a term in each note is linked, to keep the text readable. Terms already linked
+in the note, mentions in the term's own definition and mentions inside code spans, URLs, Markdown links and
+HTML tags are left as is. The new links are registered as mentions, same as the explicit ones.
+
+Several definitions can share the same title (e.g. in different glossary sections). The first one in the
+document order wins, so the choice doesn't depend on the order the notes were collected in.
+*/
+pub struct GlossaryDecorator {
+    root: Handle,
+}
+
+impl GlossaryDecorator {
+    pub fn new(root: Handle) -> GlossaryDecorator {
+        GlossaryDecorator { root }
+    }
+
+    fn collect_terms(node: &Node, terms: &mut HashMap<String, Handle>) {
+        for n in node.sorted_children() {
+            let title = n
+                .attributes()
+                .get(attributes::TITLE)
+                .cloned()
+                .or_else(|| n.handle().parts().last().cloned())
+                .unwrap_or_default();
+
+            if !title.trim().is_empty() && !n.notes().is_empty() {
+                terms
+                    .entry(title.trim().to_lowercase())
+                    .or_insert_with(|| n.handle().clone());
+            }
+
+            Self::collect_terms(n, terms);
+        }
+    }
+
+    fn build_pattern(terms: &HashMap<String, Handle>) -> Result<Regex> {
+        let mut titles = terms.keys().collect::<Vec<_>>();
+        titles.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+        let word = |c: Option<char>| c.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false);
+
+        let alternatives = titles
+            .iter()
+            .map(|t| {
+                format!(
+                    "{}{}{}",
+                    if word(t.chars().next()) { r"\b" } else { "" },
+                    regex::escape(t),
+                    if word(t.chars().last()) { r"\b" } else { "" },
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+
+        Ok(Regex::new(&format!("(?i){}", alternatives))?)
+    }
+
+    fn link_terms(
+        spans: &[NoteSpan],
+        pattern: &Regex,
+        markup: &Regex,
+        terms: &HashMap<String, Handle>,
+        own: &Handle,
+    ) -> (Vec<NoteSpan>, Vec<Handle>) {
+        let mut linked: Vec<Handle> = spans
+            .iter()
+            .filter_map(|s| match s {
+                NoteSpan::Link(h) => Some(h.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut result = vec![];
+        let mut added = vec![];
+
+        for s in spans {
+            let text = match s {
+                NoteSpan::Text(t) => t,
+                link => {
+                    result.push(link.clone());
+                    continue;
+                }
+            };
+
+            let protected = protected_ranges(text, markup);
+            let mut last = 0;
+
+            for m in pattern.find_iter(text) {
+                let handle = match terms.get(&m.as_str().to_lowercase()) {
+                    Some(h) => h,
+                    None => continue,
+                };
+
+                if handle == own
+                    || linked.contains(handle)
+                    || protected
+                        .iter()
+                        .any(|(s, e)| m.start() < *e && m.end() > *s)
+                {
+                    continue;
+                }
+
+                if m.start() > last {
+                    result.push(NoteSpan::Text(text[last..m.start()].to_string()));
+                }
+                result.push(NoteSpan::Link(handle.clone()));
+                linked.push(handle.clone());
+                added.push(handle.clone());
+                last = m.end();
+            }
+
+            if last < text.len() {
+                result.push(NoteSpan::Text(text[last..].to_string()));
+            }
+        }
+
+        (result, added)
+    }
+}
+
+/*@[Core/Decorators/Glossary]:
+Replacing a part of a URL or a Markdown link with another link would break the rendered Markdown, so these
+are protected the same way as the code: bare URLs, autolinks and HTML tags (`<...>`), inline and reference
+links and images (`[text](url)`, `[text][ref]`). The link text is protected too, it's already a link.
+*/
+const MARKUP: &str =
+    r"!?\[[^\]]*\](?:\([^)]*\)|\[[^\]]*\])|<[^<>\s][^<>]*>|\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s<>()]+";
+
+fn protected_ranges(text: &str, markup: &Regex) -> Vec<(usize, usize)> {
+    let mut ranges = code_ranges(text);
+
+    let markup_ranges = markup
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .filter(|(s, _)| !ranges.iter().any(|(cs, ce)| s >= cs && s < ce))
+        .collect::<Vec<_>>();
+
+    ranges.extend(markup_ranges);
+    ranges
+}
+
+/*@[Core/Decorators/Glossary]:
+Code spans are delimited by the runs of backticks of the same length, which covers both the inline code
+and the fenced code blocks. An unmatched run of backticks is just a text.
+*/
+fn code_ranges(text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let run = |from: usize| bytes[from..].iter().take_while(|b| **b == b'`').count();
+
+    let mut ranges = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+
+        let open = run(i);
+        let mut j = i + open;
+        let mut close = None;
+
+        while j < bytes.len() {
+            if bytes[j] == b'`' {
+                let n = run(j);
+                if n == open {
+                    close = Some(j + n);
+                    break;
+                }
+                j += n;
+            } else {
+                j += 1;
+            }
+        }
+
+        match close {
+            Some(end) => {
+                ranges.push((i, end));
+                i = end;
+            }
+            None => i += open,
+        }
+    }
+
+    ranges
+}
+
+impl Decorator for GlossaryDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        let mut terms = HashMap::new();
+
+        match tree.find_node(&self.root) {
+            Some(glossary) => Self::collect_terms(glossary, &mut terms),
+            None => return Ok(()),
+        }
+
+        if terms.is_empty() {
+            return Ok(());
+        }
+
+        let pattern = Self::build_pattern(&terms)?;
+        let markup = Regex::new(MARKUP)?;
+        let mentions = RefCell::new(vec![]);
+
+        tree.visit_mut(&|node: &mut Node| {
+            let own = node.handle().clone();
+
+            for n in node.notes_mut() {
+                let (spans, added) = Self::link_terms(n.spans(), &pattern, &markup, &terms, &own);
+                *n.spans_mut() = spans;
+
+                mentions
+                    .borrow_mut()
+                    .extend(added.into_iter().map(|h| (h, own.clone())));
+            }
+
+            Ok(())
+        })?;
+
+        for (to, from) in mentions.into_inner() {
+            tree.register_mention(&to, &from);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::Note;
+
+    use super::*;
+
+    fn add_note(tree: &mut Node, handle: &str, spans: Vec<NoteSpan>) {
+        tree.add_note(
+            &Handle::from_str(handle).unwrap(),
+            Note::new(FileLocation::new_relative("src/lib.rs", 1), spans),
+        );
+    }
+
+    fn text(s: &str) -> NoteSpan {
+        NoteSpan::Text(s.to_string())
+    }
+
+    fn link(s: &str) -> NoteSpan {
+        NoteSpan::Link(Handle::from_str(s).unwrap())
+    }
+
+    #[test]
+    fn link_glossary_terms() {
+        let mut tree = Node::root();
+
+        add_note(
+            &mut tree,
+            "Glossary/Settlement Window",
+            vec![text("The Settlement Window is a period of time.")],
+        );
+        add_note(&mut tree, "Glossary/Settlement", vec![text("Settling.")]);
+        add_note(
+            &mut tree,
+            "Glossary/Payout",
+            vec![text("Money sent after a settlement window.")],
+        );
+        add_note(
+            &mut tree,
+            "Billing",
+            vec![
+                text("Payouts wait for the settlement window, "),
+                link("Glossary/Payout"),
+                text(" follows the `settlement` and the settlement window again."),
+            ],
+        );
+
+        GlossaryDecorator::new(Handle::from_str("Glossary").unwrap())
+            .decorate(&mut tree)
+            .unwrap();
+
+        let billing = tree
+            .find_node(&Handle::from_str("Billing").unwrap())
+            .unwrap();
+        assert_eq!(
+            &vec![
+                text("Payouts wait for the "),
+                link("Glossary/Settlement Window"),
+                text(", "),
+                link("Glossary/Payout"),
+                text(" follows the `settlement` and the settlement window again."),
+            ],
+            billing.notes()[0].spans()
+        );
+
+        let window = tree
+            .find_node(&Handle::from_str("Glossary/Settlement Window").unwrap())
+            .unwrap();
+        assert_eq!(
+            &vec![text("The Settlement Window is a period of time.")],
+            window.notes()[0].spans()
+        );
+        assert!(window
+            .mentions()
+            .contains(&Handle::from_str("Billing").unwrap()));
+        assert!(window
+            .mentions()
+            .contains(&Handle::from_str("Glossary/Payout").unwrap()));
+    }
+
+    #[test]
+    fn skip_markup() {
+        let mut tree = Node::root();
+
+        add_note(&mut tree, "Glossary/Payout", vec![text("Money sent.")]);
+        add_note(&mut tree, "Glossary/Billing/Window", vec![text("First.")]);
+        add_note(&mut tree, "Glossary/Shipping/Window", vec![text("Second.")]);
+
+        let body = "See https://x.org/payout, <https://x.org/payout>, <b title=\"payout\">, \
+            [payout flow](https://x.org) and [payout][ref] ";
+        add_note(
+            &mut tree,
+            "Billing",
+            vec![text(&format!("{}before the window.", body))],
+        );
+
+        GlossaryDecorator::new(Handle::from_str("Glossary").unwrap())
+            .decorate(&mut tree)
+            .unwrap();
+
+        let billing = tree
+            .find_node(&Handle::from_str("Billing").unwrap())
+            .unwrap();
+        assert_eq!(
+            &vec![
+                text(&format!("{}before the ", body)),
+                link("Glossary/Billing/Window"),
+                text("."),
+            ],
+            billing.notes()[0].spans()
+        );
+    }
+
+    #[test]
+    fn find_code_ranges() {
+        assert_eq!(vec![(2, 5), (6, 15)], code_ranges("a `b` ```\nc\n``` d"));
+        assert_eq!(vec![(4, 7)], code_ranges("``a `b` c"));
+    }
+}
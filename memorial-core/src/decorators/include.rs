@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::decorators::Decorator;
+use crate::model::attributes;
+use crate::model::file_location::FileLocation;
+use crate::model::handle::Handle;
+use crate::model::note::{Note, NoteSpan};
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Include]:
+Include decorator inserts the contents of external Markdown files into the nodes as notes, for the content
+that is painful to write in the comments, like diagrams and large tables. The files are set either with
+`{include:path}` attribute (a comma-separated list) or in the configuration, and are read relative to
+the scanner root.
+
+The included notes point at the fragment files (line 1), so the other decorators (like external links)
+treat them the same way as the collected notes. A missing file is an error, as the document would be
+silently incomplete otherwise. So is a file outside of the scanner root (an absolute path or the one escaping
+with `..`), as it's neither a part of the project nor something the external links can point at.
+*/
+pub struct IncludeDecorator {
+    root: PathBuf,
+    files: HashMap<Handle, Vec<String>>,
+}
+
+impl IncludeDecorator {
+    pub fn new(root: PathBuf, files: HashMap<Handle, Vec<String>>) -> IncludeDecorator {
+        IncludeDecorator { root, files }
+    }
+
+    fn read(&self, path: &str) -> Result<Note> {
+        if Path::new(path).has_root() {
+            return Err(anyhow!(
+                "Failed to include {}: absolute paths are not allowed",
+                path
+            ));
+        }
+
+        let root = self
+            .root
+            .canonicalize()
+            .with_context(|| format!("Failed to include {}", path))?;
+        let full_path = root
+            .join(path)
+            .canonicalize()
+            .with_context(|| format!("Failed to include {}", path))?;
+        let relative = full_path.strip_prefix(&root).map_err(|_| {
+            anyhow!(
+                "Failed to include {}: the file is outside of the scanner root",
+                path
+            )
+        })?;
+
+        let contents = fs::read_to_string(&full_path)
+            .with_context(|| format!("Failed to include {}", path))?;
+
+        Ok(Note::new(
+            FileLocation::new_relative(relative, 1),
+            vec![NoteSpan::Text(contents.trim_end().to_string())],
+        ))
+    }
+}
+
+impl Decorator for IncludeDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        let includes = RefCell::new(self.files.clone());
+
+        tree.visit(&|node: &Node| {
+            if let Some(paths) = node.attributes().get(attributes::INCLUDE) {
+                includes
+                    .borrow_mut()
+                    .entry(node.handle().clone())
+                    .or_default()
+                    .extend(
+                        paths
+                            .split(',')
+                            .map(|p| p.trim().to_string())
+                            .filter(|p| !p.is_empty()),
+                    );
+            }
+            Ok(())
+        })?;
+
+        for (handle, paths) in includes.into_inner() {
+            for p in paths {
+                tree.add_note(&handle, self.read(&p)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn include_fragments() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/a.md"), "| a | b |\n|---|---|\n").unwrap();
+        fs::write(root.join("docs/b.md"), "```mermaid\ngraph TD\n```\n").unwrap();
+
+        let mut tree = Node::root();
+        tree.merge_attributes(
+            &Handle::from_str("Orders").unwrap(),
+            HashMap::from([(
+                attributes::INCLUDE.to_string(),
+                "docs/a.md, docs/b.md".to_string(),
+            )]),
+        );
+
+        let decorator = IncludeDecorator::new(
+            root.clone(),
+            HashMap::from([(
+                Handle::from_str("Billing").unwrap(),
+                vec!["docs/a.md".to_string()],
+            )]),
+        );
+        decorator.decorate(&mut tree).unwrap();
+
+        let orders = tree
+            .find_node(&Handle::from_str("Orders").unwrap())
+            .unwrap();
+        assert_eq!(2, orders.notes().len());
+        assert_eq!(
+            &FileLocation::new_relative("docs/a.md", 1),
+            orders.notes()[0].location()
+        );
+        assert_eq!(
+            &vec![NoteSpan::Text("```mermaid\ngraph TD\n```".to_string())],
+            orders.notes()[1].spans()
+        );
+
+        let billing = tree
+            .find_node(&Handle::from_str("Billing").unwrap())
+            .unwrap();
+        assert_eq!(1, billing.notes().len());
+
+        let missing = IncludeDecorator::new(
+            root,
+            HashMap::from([(Handle::ROOT, vec!["docs/missing.md".to_string()])]),
+        );
+        assert!(missing.decorate(&mut tree).is_err());
+    }
+
+    #[test]
+    fn reject_files_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(dir.path().join("secret.md"), "secret").unwrap();
+
+        let outside = dir.path().join("secret.md").to_str().unwrap().to_string();
+
+        for path in ["../secret.md", outside.as_str()] {
+            let decorator = IncludeDecorator::new(
+                root.clone(),
+                HashMap::from([(Handle::ROOT, vec![path.to_string()])]),
+            );
+            assert!(decorator.decorate(&mut Node::root()).is_err());
+        }
+    }
+}
@@ -1,49 +1,179 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
 use std::string::ToString;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use strfmt::Format;
 use url::Url;
 
 use crate::decorators::Decorator;
+use crate::git::GitInfo;
 use crate::model::file_location::{FileLocation, FilePath};
 use crate::model::tree::Node;
 
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum LinkProvider {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    AzureDevOps,
+    Gitea,
+    Sourcehut,
+}
+
+impl FromStr for LinkProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<LinkProvider> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(LinkProvider::GitHub),
+            "gitlab" => Ok(LinkProvider::GitLab),
+            "bitbucket" => Ok(LinkProvider::Bitbucket),
+            "azure" | "azure-devops" => Ok(LinkProvider::AzureDevOps),
+            "gitea" | "forgejo" => Ok(LinkProvider::Gitea),
+            "sourcehut" | "srht" => Ok(LinkProvider::Sourcehut),
+            _ => Err(anyhow!("Unknown links provider `{}`", s)),
+        }
+    }
+}
+
+impl LinkProvider {
+    fn detect(root: &str) -> Option<LinkProvider> {
+        [
+            ("github", LinkProvider::GitHub),
+            ("gitlab", LinkProvider::GitLab),
+            ("bitbucket", LinkProvider::Bitbucket),
+            ("dev.azure.com", LinkProvider::AzureDevOps),
+            ("visualstudio.com", LinkProvider::AzureDevOps),
+            ("codeberg.org", LinkProvider::Gitea),
+            ("sr.ht", LinkProvider::Sourcehut),
+        ]
+        .into_iter()
+        .find(|(host, _)| root.contains(host))
+        .map(|(_, p)| p)
+    }
+
+    /*@[Core/Decorators/Links]:
+    Azure DevOps tells the commits and the branches apart (`GC` and `GB` version prefixes), so without
+    a commit to pin the links to, its preset points at the branch instead.
+    */
+    fn format(&self, pinned: bool) -> &str {
+        match self {
+            LinkProvider::AzureDevOps if !pinned => {
+                "{root}?path=/{path}&version=GB{branch}&line={line}"
+            }
+            LinkProvider::GitHub => "{root}/blob/{commit}/{path}#L{line}",
+            LinkProvider::GitLab => "{root}/-/blob/{commit}/{path}#L{line}",
+            LinkProvider::Bitbucket => "{root}/src/{commit}/{path}#lines-{line}",
+            LinkProvider::AzureDevOps => "{root}?path=/{path}&version=GC{commit}&line={line}",
+            LinkProvider::Gitea => "{root}/src/commit/{commit}/{path}#L{line}",
+            LinkProvider::Sourcehut => "{root}/tree/{commit}/item/{path}#L{line}",
+        }
+    }
+}
+
 pub struct LinksDecorator {
     root: String,
     format: String,
+    commit: String,
+    branch: String,
+    remote: String,
+    prefix: String,
 }
 
 const DEFAULT_FORMAT: &str = "{root}/{path}";
+const DEFAULT_BRANCH: &str = "master";
+
+/*@[Core/Decorators/Links]:
+Path segments are percent-encoded before being put into the URL, as the paths with spaces or `#` would
+otherwise produce broken links. Slashes between the segments are kept, all the providers expect them.
+*/
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'/')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
 
 impl LinksDecorator {
-    pub fn new(root: String, format: Option<String>) -> Result<LinksDecorator> {
+    /*@[Core/Decorators/Links]:
+    The links can use the details of the local Git repository: `{commit}`, `{branch}` and `{remote}`
+    (the web URL of `origin`). Known hosting platforms get commit-pinned permalinks by default, so the
+    links keep pointing to the right lines as the files change. Without a repository, `{commit}` falls
+    back to the branch, and the branch falls back to `master`.
+
+    The format is resolved in order: an explicit format, a preset of the configured provider, a preset
+    of the provider detected from the root URL, and finally a plain `{root}/{path}`.
+
+    When the root is derived from the Git remote, the paths are made relative to the repository (instead
+    of the scanned directory, see @[Core/Git]), as this is what the repository URL expects.
+    An explicitly set root is used as is, so it can point at the scanned directory itself.
+    */
+    pub fn new(
+        root: Option<String>,
+        format: Option<String>,
+        provider: Option<LinkProvider>,
+        git: GitInfo,
+    ) -> Result<LinksDecorator> {
+        let prefix = match root {
+            Some(_) => String::new(),
+            None => git.prefix.clone().unwrap_or_default(),
+        };
+
+        let root = root.or_else(|| git.remote.clone()).ok_or_else(|| {
+            anyhow!("Links root is not set and can't be resolved from the Git remote")
+        })?;
+
         let format = format
-            .or_else(|| Self::resolve_format(&root))
+            .or_else(|| {
+                provider
+                    .or_else(|| LinkProvider::detect(&root))
+                    .map(|p| p.format(git.commit.is_some()).to_string())
+            })
             .unwrap_or(DEFAULT_FORMAT.to_string());
 
+        let branch = git.branch.unwrap_or(DEFAULT_BRANCH.to_string());
+
         Ok(LinksDecorator {
             root: root.trim_end_matches('/').to_string(),
             format,
+            commit: git.commit.unwrap_or(branch.clone()),
+            branch,
+            remote: git.remote.unwrap_or_default(),
+            prefix,
         })
     }
 
-    fn resolve_format(root: &str) -> Option<String> {
-        if root.contains("github") || root.contains("gitlab") {
-            return Some("{root}/blob/master/{path}#L{line}".to_string());
-        }
-
-        None
+    fn encode_path(path: &Path) -> String {
+        path.iter()
+            .map(|s| utf8_percent_encode(&s.to_string_lossy(), PATH_SEGMENT).to_string())
+            .collect::<Vec<_>>()
+            .join("/")
     }
 
     fn wrap(&self, l: &mut FileLocation) -> Result<()> {
         match l.path() {
             FilePath::Relative(p) => {
                 let line_str = l.line().to_string();
+                let path = Self::encode_path(&Path::new(&self.prefix).join(p));
                 let vars: HashMap<String, &str> = HashMap::from([
                     ("root".to_string(), self.root.as_str()),
-                    ("path".to_string(), p.to_str().unwrap()),
+                    ("path".to_string(), &path),
                     ("line".to_string(), &line_str),
+                    ("commit".to_string(), self.commit.as_str()),
+                    ("branch".to_string(), self.branch.as_str()),
+                    ("remote".to_string(), self.remote.as_str()),
                 ]);
 
                 let url = Url::parse(&self.format.format(&vars)?)?;
@@ -73,3 +203,167 @@ impl Decorator for LinksDecorator {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::model::handle::Handle;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn decorate(decorator: LinksDecorator) -> String {
+        decorate_path(decorator, "src/lib.rs")
+    }
+
+    fn decorate_path(decorator: LinksDecorator, path: &str) -> String {
+        let mut tree = Node::root();
+        let handle = Handle::from_str("a").unwrap();
+
+        tree.add_note(
+            &handle,
+            Note::new(
+                FileLocation::new_relative(path, 12),
+                vec![NoteSpan::Text("note".to_string())],
+            ),
+        );
+
+        decorator.decorate(&mut tree).unwrap();
+
+        tree.find_node(&handle).unwrap().notes()[0]
+            .location()
+            .path()
+            .to_string()
+    }
+
+    #[test]
+    fn pin_links_to_commit() {
+        let git = GitInfo {
+            commit: Some("abc123".to_string()),
+            branch: Some("main".to_string()),
+            remote: Some("https://github.com/Kostassoid/memorial".to_string()),
+            prefix: None,
+        };
+
+        assert_eq!(
+            "https://github.com/Kostassoid/memorial/blob/abc123/src/lib.rs#L12",
+            decorate(LinksDecorator::new(None, None, None, git.clone()).unwrap())
+        );
+
+        assert_eq!(
+            "https://example.com/memorial/tree/main/src/lib.rs?from=https://github.com/Kostassoid/memorial",
+            decorate(
+                LinksDecorator::new(
+                    Some("https://example.com/memorial/".to_string()),
+                    Some("{root}/tree/{branch}/{path}?from={remote}".to_string()),
+                    None,
+                    git
+                )
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn prefix_paths_in_nested_root() {
+        let git = GitInfo {
+            commit: Some("abc123".to_string()),
+            branch: Some("main".to_string()),
+            remote: Some("https://github.com/Kostassoid/memorial".to_string()),
+            prefix: Some("services/billing/".to_string()),
+        };
+
+        assert_eq!(
+            "https://github.com/Kostassoid/memorial/blob/abc123/services/billing/src/lib.rs#L12",
+            decorate(LinksDecorator::new(None, None, None, git.clone()).unwrap())
+        );
+
+        assert_eq!(
+            "https://example.com/billing/blob/abc123/src/lib.rs#L12",
+            decorate(
+                LinksDecorator::new(
+                    Some("https://example.com/billing".to_string()),
+                    Some(LinkProvider::GitHub.format(true).to_string()),
+                    None,
+                    git
+                )
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn fall_back_without_repository() {
+        assert_eq!(
+            "https://github.com/Kostassoid/memorial/blob/master/src/lib.rs#L12",
+            decorate(
+                LinksDecorator::new(
+                    Some("https://github.com/Kostassoid/memorial".to_string()),
+                    None,
+                    None,
+                    GitInfo::default()
+                )
+                .unwrap()
+            )
+        );
+
+        assert_eq!(
+            "https://dev.azure.com/org/project/_git/repo?path=/src/lib.rs&version=GBmaster&line=12",
+            decorate(
+                LinksDecorator::new(
+                    Some("https://dev.azure.com/org/project/_git/repo".to_string()),
+                    None,
+                    None,
+                    GitInfo::default()
+                )
+                .unwrap()
+            )
+        );
+
+        assert!(LinksDecorator::new(None, None, None, GitInfo::default()).is_err());
+    }
+
+    #[test]
+    fn use_provider_presets() {
+        let git = GitInfo {
+            commit: Some("abc123".to_string()),
+            branch: None,
+            remote: None,
+            prefix: None,
+        };
+
+        let link = |root: &str, provider: Option<&str>| {
+            decorate_path(
+                LinksDecorator::new(
+                    Some(root.to_string()),
+                    None,
+                    provider.map(|p| p.parse().unwrap()),
+                    git.clone(),
+                )
+                .unwrap(),
+                "src/my docs/#1.rs",
+            )
+        };
+
+        assert_eq!(
+            "https://bitbucket.org/team/repo/src/abc123/src/my%20docs/%231.rs#lines-12",
+            link("https://bitbucket.org/team/repo", None)
+        );
+        assert_eq!(
+            "https://dev.azure.com/org/project/_git/repo?path=/src/my%20docs/%231.rs&version=GCabc123&line=12",
+            link("https://dev.azure.com/org/project/_git/repo", None)
+        );
+        assert_eq!(
+            "https://git.example.com/team/repo/src/commit/abc123/src/my%20docs/%231.rs#L12",
+            link("https://git.example.com/team/repo", Some("gitea"))
+        );
+        assert_eq!(
+            "https://git.sr.ht/~team/repo/tree/abc123/item/src/my%20docs/%231.rs#L12",
+            link("https://git.sr.ht/~team/repo", None)
+        );
+        assert_eq!(
+            "https://gitlab.example.com/team/repo/-/blob/abc123/src/my%20docs/%231.rs#L12",
+            link("https://gitlab.example.com/team/repo", Some("gitlab"))
+        );
+        assert!("unknown".parse::<LinkProvider>().is_err());
+    }
+}
@@ -2,7 +2,15 @@ use anyhow::Result;
 
 use crate::model::tree::Node;
 
+pub mod audience;
+pub mod blame;
+pub mod filter;
+pub mod glossary;
+pub mod include;
 pub mod links;
+pub mod numbering;
+pub mod owners;
+pub mod registry;
 pub mod root;
 
 pub trait Decorator {
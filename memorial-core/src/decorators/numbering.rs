@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::decorators::Decorator;
+use crate::model::attributes;
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Numbering]:
+Numbering decorator assigns hierarchical numbers (`1`, `1.2`, `1.2.3`) to the nodes, following the same
+order the children are rendered in (see @[Core/Model/Tree]). The numbers are stored as node attributes,
+so the renderers can use them consistently in the headings, table of contents and links.
+
+Numbering can start at a given depth (1 being the top level sections), e.g. to leave the top level sections
+unnumbered when they represent separate parts of the document. In this case the numbering restarts
+under each of the unnumbered sections.
+*/
+pub struct NumberingDecorator {
+    start_depth: usize,
+}
+
+impl NumberingDecorator {
+    pub fn new(start_depth: usize) -> NumberingDecorator {
+        NumberingDecorator {
+            start_depth: start_depth.max(1),
+        }
+    }
+
+    fn collect(
+        &self,
+        node: &Node,
+        depth: usize,
+        prefix: Option<&str>,
+        numbers: &mut Vec<(Handle, String)>,
+    ) {
+        for (i, child) in node.sorted_children().into_iter().enumerate() {
+            if depth + 1 < self.start_depth {
+                self.collect(child, depth + 1, None, numbers);
+                continue;
+            }
+
+            let number = match prefix {
+                Some(p) => format!("{}.{}", p, i + 1),
+                None => (i + 1).to_string(),
+            };
+
+            self.collect(child, depth + 1, Some(&number), numbers);
+            numbers.push((child.handle().clone(), number));
+        }
+    }
+}
+
+impl Decorator for NumberingDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        let mut numbers = vec![];
+        self.collect(tree, 0, None, &mut numbers);
+
+        for (handle, number) in numbers {
+            tree.merge_attributes(
+                &handle,
+                HashMap::from([(attributes::NUMBER.to_string(), number)]),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn build_tree() -> Node {
+        let mut tree = Node::root();
+
+        for h in [
+            "Intro",
+            "Design/Storage",
+            "Design/API/REST",
+            "Design/API/gRPC",
+        ] {
+            tree.add_note(
+                &Handle::from_str(h).unwrap(),
+                Note::new(
+                    FileLocation::new_relative("src/lib.rs", 1),
+                    vec![NoteSpan::Text("note".to_string())],
+                ),
+            );
+        }
+
+        tree.merge_attributes(
+            &Handle::from_str("Intro").unwrap(),
+            HashMap::from([(attributes::ORDER.to_string(), "1".to_string())]),
+        );
+
+        tree
+    }
+
+    fn number_of(tree: &Node, handle: &str) -> Option<String> {
+        tree.find_node(&Handle::from_str(handle).unwrap())
+            .unwrap()
+            .attributes()
+            .get(attributes::NUMBER)
+            .cloned()
+    }
+
+    #[test]
+    fn number_nodes_in_rendering_order() {
+        let mut tree = build_tree();
+        NumberingDecorator::new(1).decorate(&mut tree).unwrap();
+
+        assert_eq!(None, tree.attributes().get(attributes::NUMBER));
+        assert_eq!(Some("1".to_string()), number_of(&tree, "Intro"));
+        assert_eq!(Some("2".to_string()), number_of(&tree, "Design"));
+        assert_eq!(Some("2.1".to_string()), number_of(&tree, "Design/API"));
+        assert_eq!(
+            Some("2.1.1".to_string()),
+            number_of(&tree, "Design/API/REST")
+        );
+        assert_eq!(
+            Some("2.1.2".to_string()),
+            number_of(&tree, "Design/API/gRPC")
+        );
+        assert_eq!(Some("2.2".to_string()), number_of(&tree, "Design/Storage"));
+    }
+
+    #[test]
+    fn number_nodes_from_depth() {
+        let mut tree = build_tree();
+        NumberingDecorator::new(2).decorate(&mut tree).unwrap();
+
+        assert_eq!(None, number_of(&tree, "Intro"));
+        assert_eq!(None, number_of(&tree, "Design"));
+        assert_eq!(Some("1".to_string()), number_of(&tree, "Design/API"));
+        assert_eq!(Some("1.2".to_string()), number_of(&tree, "Design/API/gRPC"));
+        assert_eq!(Some("2".to_string()), number_of(&tree, "Design/Storage"));
+    }
+}
@@ -0,0 +1,325 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use glob::{MatchOptions, Pattern};
+
+use crate::decorators::Decorator;
+use crate::model::attributes;
+use crate::model::file_location::FilePath;
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Owners]:
+Owners decorator resolves the files of the notes against `CODEOWNERS` and aggregates the owners per node,
+so the readers know whom to ask about the documented concept. Only the notes of the node itself are
+considered (along with the extra locations, the references without a note body), the owners of the child
+nodes are shown in their own sections.
+
+Both GitHub and GitLab syntax is supported. The last matching rule wins, although in GitLab files each
+section is evaluated separately and the owners from all the matching sections are combined.
+*/
+pub struct OwnersDecorator {
+    owners: CodeOwners,
+}
+
+pub const LOCATIONS: [&str; 4] = [
+    "CODEOWNERS",
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+impl OwnersDecorator {
+    pub fn new(owners: CodeOwners) -> OwnersDecorator {
+        OwnersDecorator { owners }
+    }
+
+    /*@[Core/Decorators/Owners]:
+    Without an explicit path, `CODEOWNERS` is looked up in the same locations GitHub and GitLab use.
+    The patterns are matched against the paths relative to the scanner root, so the root is expected
+    to be the root of the repository.
+    */
+    pub fn locate<P: AsRef<Path>>(root: P, path: Option<String>) -> Result<OwnersDecorator> {
+        let path = match path {
+            Some(p) => root.as_ref().join(p),
+            None => LOCATIONS
+                .iter()
+                .map(|l| root.as_ref().join(l))
+                .find(|p| p.is_file())
+                .ok_or_else(|| anyhow!("CODEOWNERS file not found"))?,
+        };
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+
+        Ok(OwnersDecorator::new(CodeOwners::parse(&contents)?))
+    }
+}
+
+impl Decorator for OwnersDecorator {
+    fn decorate(&self, tree: &mut Node) -> Result<()> {
+        let found = RefCell::new(Vec::<(Handle, Vec<String>)>::new());
+
+        tree.visit(&|node: &Node| {
+            let mut owners: Vec<String> = vec![];
+
+            let locations = node
+                .notes()
+                .iter()
+                .map(|n| n.location())
+                .chain(node.extra().iter());
+
+            for l in locations {
+                if let FilePath::Relative(p) = l.path() {
+                    for o in self.owners.owners_of(p) {
+                        if !owners.contains(&o) {
+                            owners.push(o);
+                        }
+                    }
+                }
+            }
+
+            if !owners.is_empty() {
+                found.borrow_mut().push((node.handle().clone(), owners));
+            }
+
+            Ok(())
+        })?;
+
+        for (handle, owners) in found.into_inner() {
+            tree.merge_attributes(
+                &handle,
+                HashMap::from([(attributes::OWNERS.to_string(), owners.join(", "))]),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CodeOwners {
+    sections: Vec<Vec<Rule>>,
+}
+
+struct Rule {
+    patterns: Vec<Pattern>,
+    owners: Vec<String>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl CodeOwners {
+    pub fn parse(contents: &str) -> Result<CodeOwners> {
+        let mut sections = vec![vec![]];
+        let mut defaults: Vec<String> = vec![];
+
+        for line in contents.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = Self::section_header(line) {
+                sections.push(vec![]);
+                defaults = header;
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next().unwrap();
+            let owners = parts
+                .take_while(|p| !p.starts_with('#'))
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>();
+
+            sections.last_mut().unwrap().push(Rule {
+                patterns: Self::to_globs(pattern)?,
+                owners: if owners.is_empty() {
+                    defaults.clone()
+                } else {
+                    owners
+                },
+            });
+        }
+
+        Ok(CodeOwners { sections })
+    }
+
+    pub fn owners_of<P: AsRef<Path>>(&self, path: P) -> Vec<String> {
+        let path = path.as_ref();
+        let mut owners: Vec<String> = vec![];
+
+        for section in &self.sections {
+            let rule = section.iter().rev().find(|r| {
+                r.patterns
+                    .iter()
+                    .any(|p| p.matches_path_with(path, MATCH_OPTIONS))
+            });
+
+            for o in rule.iter().flat_map(|r| r.owners.iter()) {
+                if !owners.contains(o) {
+                    owners.push(o.clone());
+                }
+            }
+        }
+
+        owners
+    }
+
+    /*@[Core/Decorators/Owners]:
+    GitLab section headers look like `[Section]`, optionally marked as optional (`^[Section]`), requiring
+    a number of approvals (`[Section][2]`) and followed by the default owners of the section.
+    */
+    fn section_header(line: &str) -> Option<Vec<String>> {
+        let header = line.strip_prefix('^').unwrap_or(line);
+        if !header.starts_with('[') {
+            return None;
+        }
+
+        let end = header.rfind(']')?;
+        Some(
+            header[end + 1..]
+                .split_whitespace()
+                .take_while(|p| !p.starts_with('#'))
+                .map(|p| p.to_string())
+                .collect(),
+        )
+    }
+
+    /*@[Core/Decorators/Owners]:
+    `CODEOWNERS` patterns follow `.gitignore` rules, which are translated into globs:
+    patterns without a slash (except the trailing one) match at any depth, a leading slash anchors
+    the pattern to the root, and a pattern matching a directory matches everything inside it.
+    The only exception is a trailing `*` segment, which matches the files directly in the directory only.
+    */
+    fn to_globs(pattern: &str) -> Result<Vec<Pattern>> {
+        let directory = pattern.ends_with('/');
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.contains('/');
+        let trimmed = trimmed.trim_start_matches('/');
+
+        let base = if anchored || trimmed.starts_with("**") {
+            trimmed.to_string()
+        } else {
+            format!("**/{}", trimmed)
+        };
+
+        let mut globs = vec![];
+
+        if !directory {
+            globs.push(base.clone());
+        }
+
+        if !base.ends_with("/*") {
+            globs.push(format!("{}/**", base));
+        }
+
+        globs
+            .iter()
+            .map(|g| Pattern::new(g).map_err(|e| anyhow!("Invalid pattern {}: {}", pattern, e)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    #[test]
+    fn resolve_owners() {
+        let owners = CodeOwners::parse(
+            r"
+# GitHub style
+*                 @global
+*.js              @js-owner # inline comment
+/build/logs/      @doghouse
+docs/*            docs@example.com
+apps/             @octocat
+/scripts/         @doctocat @octocat
+/apps/github
+
+[Billing] @team-billing
+/billing/
+/billing/legacy/  @team-legacy
+",
+        )
+        .unwrap();
+
+        let check = |path: &str, expected: &[&str]| {
+            assert_eq!(
+                expected.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                owners.owners_of(path),
+                "{}",
+                path
+            )
+        };
+
+        check("README.md", &["@global"]);
+        check("src/app.js", &["@js-owner"]);
+        check("build/logs/debug.log", &["@doghouse"]);
+        check("build/logs/2023/debug.log", &["@doghouse"]);
+        check("docs/intro.md", &["docs@example.com"]);
+        check("docs/guide/intro.md", &["@global"]);
+        check("web/apps/main.rs", &["@octocat"]);
+        check("scripts/build.sh", &["@doctocat", "@octocat"]);
+        check("apps/github/main.rs", &[]);
+        check("billing/invoice.rs", &["@global", "@team-billing"]);
+        check("billing/legacy/invoice.rs", &["@global", "@team-legacy"]);
+    }
+
+    #[test]
+    fn aggregate_owners_per_node() {
+        let mut tree = Node::root();
+
+        for (handle, path) in [
+            ("Billing", "billing/invoice.rs"),
+            ("Billing", "billing/legacy/invoice.rs"),
+            ("Billing", "billing/tax.rs"),
+            ("Docs", "README.md"),
+        ] {
+            tree.add_note(
+                &Handle::from_str(handle).unwrap(),
+                Note::new(
+                    FileLocation::new_relative(path, 1),
+                    vec![NoteSpan::Text("note".to_string())],
+                ),
+            );
+        }
+
+        // referenced without a note body, kept as an extra location
+        tree.add_note(
+            &Handle::from_str("Payments").unwrap(),
+            Note::new(FileLocation::new_relative("billing/payments.rs", 1), vec![]),
+        );
+
+        let decorator = OwnersDecorator::new(
+            CodeOwners::parse("/billing/ @team-billing\n/billing/legacy/ @team-legacy\n").unwrap(),
+        );
+        decorator.decorate(&mut tree).unwrap();
+
+        let owners = |handle: &str| {
+            tree.find_node(&Handle::from_str(handle).unwrap())
+                .unwrap()
+                .attributes()
+                .get(attributes::OWNERS)
+                .cloned()
+        };
+
+        assert_eq!(
+            Some("@team-billing, @team-legacy".to_string()),
+            owners("Billing")
+        );
+        assert_eq!(Some("@team-billing".to_string()), owners("Payments"));
+        assert_eq!(None, owners("Docs"));
+        assert_eq!(None, tree.attributes().get(attributes::OWNERS));
+    }
+}
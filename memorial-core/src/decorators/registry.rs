@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+use crate::decorators::blame::BlameDecorator;
+use crate::decorators::glossary::GlossaryDecorator;
+use crate::decorators::include::IncludeDecorator;
+use crate::decorators::links::LinksDecorator;
+use crate::decorators::numbering::NumberingDecorator;
+use crate::decorators::owners::OwnersDecorator;
+use crate::decorators::Decorator;
+use crate::git::Repository;
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+
+/*@[Core/Decorators/Registry]:
+Registry maps decorator names to factories, so the decorators can be configured as an ordered pipeline
+of names and options, without the caller knowing about the specific implementations. Built-in decorators
+are registered by default, library users can register their own ones (or replace the built-ins).
+
+The options are passed as JSON values, as this is a common denominator for any configuration format.
+Names are case-insensitive and `_` is treated as `-`, so `external_links` and `external-links` are the same.
+*/
+pub type DecoratorFactory = Box<dyn Fn(&DecoratorContext, Value) -> Result<Box<dyn Decorator>>>;
+
+pub struct DecoratorContext {
+    pub root: PathBuf,
+}
+
+pub struct DecoratorRegistry {
+    factories: HashMap<String, DecoratorFactory>,
+}
+
+pub const BLAME: &str = "blame";
+pub const OWNERS: &str = "owners";
+pub const EXTERNAL_LINKS: &str = "external-links";
+pub const NUMBERING: &str = "numbering";
+pub const GLOSSARY: &str = "glossary";
+pub const INCLUDE: &str = "include";
+
+impl DecoratorRegistry {
+    pub fn empty() -> DecoratorRegistry {
+        DecoratorRegistry {
+            factories: HashMap::new(),
+        }
+    }
+
+    pub fn new() -> DecoratorRegistry {
+        let mut registry = Self::empty();
+        registry.register(BLAME, build_blame);
+        registry.register(OWNERS, build_owners);
+        registry.register(EXTERNAL_LINKS, build_links);
+        registry.register(NUMBERING, build_numbering);
+        registry.register(GLOSSARY, build_glossary);
+        registry.register(INCLUDE, build_include);
+        registry
+    }
+
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&DecoratorContext, Value) -> Result<Box<dyn Decorator>> + 'static,
+    {
+        self.factories
+            .insert(Self::normalize(name), Box::new(factory));
+    }
+
+    pub fn build(
+        &self,
+        name: &str,
+        context: &DecoratorContext,
+        options: Value,
+    ) -> Result<Box<dyn Decorator>> {
+        let factory = self
+            .factories
+            .get(&Self::normalize(name))
+            .ok_or_else(|| anyhow!("Unknown decorator `{}`", name))?;
+
+        factory(context, options)
+            .with_context(|| format!("Failed to configure decorator `{}`", name))
+    }
+
+    fn normalize(name: &str) -> String {
+        name.trim().to_lowercase().replace('_', "-")
+    }
+}
+
+impl Default for DecoratorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_options<T: serde::de::DeserializeOwned>(options: Value) -> Result<T> {
+    Ok(serde_json::from_value(match options {
+        Value::Null => Value::Object(Default::default()),
+        o => o,
+    })?)
+}
+
+#[derive(Deserialize)]
+struct BlameOptions {
+    #[serde(alias = "stale-after-months")]
+    stale_after_months: Option<u32>,
+}
+
+/*@[Core/Decorators/Registry]:
+Blame decorator is skipped (with a warning) outside of a Git repository, same as it skips the untracked
+files, so the same configuration works for the source archives without the history.
+*/
+fn build_blame(context: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: BlameOptions = parse_options(options)?;
+
+    let repository = match Repository::open(&context.root) {
+        Some(r) => r,
+        None => {
+            println!(
+                "Warning: {} is not in a Git repository, blame decorator is skipped",
+                context.root.display()
+            );
+            return Ok(Box::new(Skipped));
+        }
+    };
+
+    Ok(Box::new(BlameDecorator::new(
+        repository,
+        options.stale_after_months,
+    )))
+}
+
+struct Skipped;
+
+impl Decorator for Skipped {
+    fn decorate(&self, _: &mut Node) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct OwnersOptions {
+    path: Option<String>,
+}
+
+fn build_owners(context: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: OwnersOptions = parse_options(options)?;
+
+    Ok(Box::new(OwnersDecorator::locate(
+        &context.root,
+        options.path,
+    )?))
+}
+
+#[derive(Deserialize)]
+struct LinksOptions {
+    root: Option<String>,
+    format: Option<String>,
+    provider: Option<String>,
+}
+
+fn build_links(context: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: LinksOptions = parse_options(options)?;
+
+    let git = Repository::open(&context.root)
+        .map(|r| r.info())
+        .unwrap_or_default();
+
+    let provider = match options.provider {
+        Some(p) => Some(p.parse()?),
+        None => None,
+    };
+
+    Ok(Box::new(LinksDecorator::new(
+        options.root,
+        options.format,
+        provider,
+        git,
+    )?))
+}
+
+#[derive(Deserialize)]
+struct NumberingOptions {
+    #[serde(alias = "start-depth")]
+    start_depth: Option<usize>,
+}
+
+fn build_numbering(_: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: NumberingOptions = parse_options(options)?;
+
+    Ok(Box::new(NumberingDecorator::new(
+        options.start_depth.unwrap_or(1),
+    )))
+}
+
+#[derive(Deserialize)]
+struct GlossaryOptions {
+    root: Option<String>,
+}
+
+fn build_glossary(_: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: GlossaryOptions = parse_options(options)?;
+
+    Ok(Box::new(GlossaryDecorator::new(Handle::from_str(
+        options.root.as_deref().unwrap_or("Glossary"),
+    )?)))
+}
+
+#[derive(Deserialize)]
+struct IncludeOptions {
+    files: Option<HashMap<String, String>>,
+}
+
+fn build_include(context: &DecoratorContext, options: Value) -> Result<Box<dyn Decorator>> {
+    let options: IncludeOptions = parse_options(options)?;
+
+    let mut files = HashMap::new();
+    for (handle, path) in options.files.unwrap_or_default() {
+        files.insert(Handle::from_str(&handle)?, vec![path]);
+    }
+
+    Ok(Box::new(IncludeDecorator::new(context.root.clone(), files)))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    struct Marker;
+
+    impl Decorator for Marker {
+        fn decorate(&self, tree: &mut Node) -> Result<()> {
+            tree.merge_attributes(
+                &Handle::ROOT,
+                HashMap::from([("marked".to_string(), "true".to_string())]),
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn build_registered_decorators() {
+        let context = DecoratorContext {
+            root: PathBuf::from("."),
+        };
+
+        let mut registry = DecoratorRegistry::new();
+        registry.register("marker", |_, _| Ok(Box::new(Marker)));
+
+        let mut tree = Node::root();
+        registry
+            .build("Marker", &context, Value::Null)
+            .unwrap()
+            .decorate(&mut tree)
+            .unwrap();
+        assert_eq!(Some(&"true".to_string()), tree.attributes().get("marked"));
+
+        assert!(registry
+            .build(
+                "external_links",
+                &context,
+                json!({ "root": "https://github.com/Kostassoid/memorial" })
+            )
+            .is_ok());
+
+        assert!(registry
+            .build(
+                "external-links",
+                &context,
+                json!({ "root": "https://github.com/Kostassoid/memorial", "provider": "nope" })
+            )
+            .is_err());
+
+        assert!(registry.build("unknown", &context, Value::Null).is_err());
+    }
+
+    #[test]
+    fn skip_blame_without_repository() {
+        let root = tempfile::tempdir().unwrap();
+        let context = DecoratorContext {
+            root: root.path().to_path_buf(),
+        };
+
+        let mut tree = Node::root();
+        DecoratorRegistry::new()
+            .build("blame", &context, Value::Null)
+            .unwrap()
+            .decorate(&mut tree)
+            .unwrap();
+    }
+
+    #[test]
+    fn build_with_rooted_handles() {
+        let context = DecoratorContext {
+            root: PathBuf::from("."),
+        };
+
+        let mut tree = Node::root();
+        tree.add_note(
+            &Handle::from_str("Glossary/Payout").unwrap(),
+            Note::new(
+                FileLocation::new_relative("src/lib.rs", 1),
+                vec![NoteSpan::Text("Money sent.".to_string())],
+            ),
+        );
+        tree.add_note(
+            &Handle::from_str("Billing").unwrap(),
+            Note::new(
+                FileLocation::new_relative("src/lib.rs", 2),
+                vec![NoteSpan::Text("Sends a payout.".to_string())],
+            ),
+        );
+
+        DecoratorRegistry::new()
+            .build("glossary", &context, json!({ "root": "/Glossary" }))
+            .unwrap()
+            .decorate(&mut tree)
+            .unwrap();
+
+        assert!(tree
+            .find_node(&Handle::from_str("Glossary/Payout").unwrap())
+            .unwrap()
+            .mentions()
+            .contains(&Handle::from_str("Billing").unwrap()));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod api;
 pub mod collector;
 pub mod decorators;
+pub mod git;
 pub mod model;
 pub mod parser;
 pub mod renderer;
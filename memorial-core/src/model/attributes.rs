@@ -3,6 +3,56 @@ Attributes are either attached to the node or control the collection process.
 */
 pub const TITLE: &'static str = "title";
 pub const DO_NOT_COLLECT: &'static str = "do-not-collect";
+pub const ORDER: &str = "order";
+pub const PRIORITY: &str = "priority";
+pub const WEIGHT: &str = "weight";
+pub const DEPRECATED: &str = "deprecated";
+pub const SINCE: &str = "since";
+pub const AUDIENCE: &str = "audience";
+pub const ALIAS: &str = "alias";
+pub const FILE_BASE: &str = "file-base";
+pub const OWNER: &str = "owner";
+pub const TICKET: &str = "ticket";
+pub const INCLUDE: &str = "include";
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Scope {
+    /// Merged into the attributes of the node.
+    Node,
+    /// Kept with the individual note.
+    Note,
+    /// Controls the collection process and is not kept anywhere.
+    Collection,
+}
+
+/*@[Core/Model/Attributes]:
+The registry decides where an attribute declared in a quote ends up. Unknown attributes are attached to
+the node, as it has always been the case, so the custom attributes keep working for the renderers and
+decorators relying on them.
+*/
+const REGISTRY: &[(&str, Scope)] = &[
+    (TITLE, Scope::Node),
+    (ORDER, Scope::Node),
+    (INCLUDE, Scope::Node),
+    (DO_NOT_COLLECT, Scope::Collection),
+    (ALIAS, Scope::Collection),
+    (FILE_BASE, Scope::Collection),
+    (PRIORITY, Scope::Note),
+    (WEIGHT, Scope::Note),
+    (DEPRECATED, Scope::Note),
+    (SINCE, Scope::Note),
+    (AUDIENCE, Scope::Note),
+    (OWNER, Scope::Note),
+    (TICKET, Scope::Note),
+];
+
+pub fn scope_of(key: &str) -> Scope {
+    REGISTRY
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, s)| *s)
+        .unwrap_or(Scope::Node)
+}
 
 /*@[Core/Model/Attributes]:
 Some attributes are used internally to enrich collected knowledge tree with some valuable context,
@@ -14,3 +64,11 @@ pub const APP_VERSION: &'static str = "!app-version";
 pub const TIMESTAMP: &'static str = "!timestamp";
 pub const OUTPUT_FILE_NAME: &'static str = "!output-file-name";
 pub const TOC: &'static str = "!toc";
+pub const QUOTE_NOTES: &str = "!quote-notes";
+pub const ANCHORS: &str = "!anchors";
+pub const SHOW_BLAME: &str = "!show-blame";
+pub const AUTHOR: &str = "!author";
+pub const MODIFIED: &str = "!modified";
+pub const STALE: &str = "!stale";
+pub const OWNERS: &str = "!owners";
+pub const NUMBER: &str = "!number";
@@ -3,6 +3,10 @@ use std::fmt::{Display, Formatter};
 
 pub type HandlePart = String;
 
+const CURRENT: &str = ".";
+const PARENT: &str = "..";
+const ROOTED: &str = "/";
+
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 pub struct Handle {
     parts: Vec<HandlePart>,
@@ -29,20 +33,89 @@ impl Handle {
         Ok(Handle { parts })
     }
 
+    /*@[Core/Model/Handle]:
+    Outside of the collected notes there are no handle prefixes, so a leading `/` (see `resolve`) makes
+    no difference: the rooted handles from the configuration, templates, etc. are parsed as the absolute ones.
+    */
     pub fn from_str(s: &str) -> Result<Handle> {
-        let parts: Vec<_> = s.split('/').map(|p| p.trim()).collect();
+        let handle = Self::from_quote_str(s)?;
 
-        Self::from_parts(parts.into_iter().map(|s| s.to_owned()).collect())
+        match handle.is_rooted() {
+            true => handle.resolve(&Handle::ROOT),
+            false => Ok(handle),
+        }
     }
 
-    //todo: incomplete
+    pub(crate) fn from_quote_str(s: &str) -> Result<Handle> {
+        let (rooted, s) = match s.trim_start().strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let mut parts: Vec<_> = s.split('/').map(|p| p.trim().to_owned()).collect();
+
+        if rooted {
+            parts.insert(0, ROOTED.to_owned());
+        }
+
+        Self::from_parts(parts)
+    }
+
+    /*@[Core/Model/Handle]:
+    URL safe representation of a handle is made of the slugified parts joined with `+`. Slugs keep
+    only (Unicode) letters, digits, `-` and `_`, so different handles can end up with the same
+    representation (e.g. `C#` and `C`). Resolving these collisions requires knowing all the handles,
+    so it's up to the renderers.
+    */
     pub fn as_url_safe_string(&self) -> String {
         self.parts
+            .iter()
+            .map(|p| slugify(p))
+            .collect::<Vec<_>>()
             .join("+")
-            .replace(" ", "-")
-            .replace("\t", "-")
-            .replace("\n", "-")
-            .to_lowercase()
+    }
+
+    pub fn is_relative(&self) -> bool {
+        matches!(
+            self.parts.first().map(|p| p.as_str()),
+            Some(CURRENT) | Some(PARENT)
+        )
+    }
+
+    pub fn is_rooted(&self) -> bool {
+        self.parts.first().map(|p| p.as_str()) == Some(ROOTED)
+    }
+
+    /*@[Core/Model/Handle]:
+    Relative handles start with `.` (the base itself) or `..` (the parent of the base), like the relative
+    paths in a file system. Absolute handles are returned as is, regardless of the base. Handles starting
+    with `/` are explicitly rooted, which only matters when the handles are prefixed (see @[Core/Collector]),
+    so resolving just drops the leading `/`.
+    */
+    pub fn resolve(&self, base: &Handle) -> Result<Handle> {
+        if self.is_rooted() {
+            return Handle::from_parts(self.parts[1..].to_vec());
+        }
+
+        if !self.is_relative() {
+            return Ok(self.clone());
+        }
+
+        let mut parts = base.parts.clone();
+
+        for p in &self.parts {
+            match p.as_str() {
+                CURRENT => {}
+                PARENT => {
+                    if parts.pop().is_none() {
+                        return Err(anyhow!("Handle `{}` points above the root", self));
+                    }
+                }
+                _ => parts.push(p.clone()),
+            }
+        }
+
+        Handle::from_parts(parts)
     }
 
     pub fn join(&self, part: HandlePart) -> Result<Handle> {
@@ -58,3 +131,64 @@ impl Display for Handle {
         f.write_str(&self.parts.join(" / "))
     }
 }
+
+pub fn slugify(s: &str) -> String {
+    s.trim()
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn url_safe_representation() {
+        let check = |h: &str| Handle::from_str(h).unwrap().as_url_safe_string();
+
+        assert_eq!("a+b+c", check("a/b/c"));
+        assert_eq!(
+            "domain-rules+invariants",
+            check("Domain Rules / Invariants")
+        );
+        assert_eq!("what-if+c", check("What if?/C#"));
+        assert_eq!("функции+a_b-c--", check("Функции/(A_B-C) & 😀"));
+    }
+
+    #[test]
+    fn resolving_relative_handles() {
+        let base = Handle::from_str("Billing/Invoices").unwrap();
+        let resolve = |h: &str| {
+            Handle::from_str(h)
+                .unwrap()
+                .resolve(&base)
+                .map(|h| h.to_string())
+        };
+
+        assert_eq!(
+            "Billing / Invoices / Rounding",
+            resolve("./Rounding").unwrap()
+        );
+        assert_eq!("Billing / Taxes", resolve("../Taxes").unwrap());
+        assert_eq!("Billing / Invoices", resolve(".").unwrap());
+        assert_eq!("Taxes", resolve("Taxes").unwrap());
+        assert_eq!("Taxes / Rates", resolve(" / Taxes/Rates").unwrap());
+        assert!(resolve("../../..").is_err());
+    }
+
+    #[test]
+    fn parsing_rooted_handles() {
+        assert_eq!(
+            Handle::from_str("Glossary").unwrap(),
+            Handle::from_str(" /Glossary").unwrap()
+        );
+        assert!(Handle::from_quote_str("/Glossary").unwrap().is_rooted());
+        assert!(Handle::from_str("/").is_err());
+    }
+}
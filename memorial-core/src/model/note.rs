@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use super::handle::Handle;
 use crate::model::file_location::FileLocation;
 
@@ -11,17 +13,66 @@ pub enum NoteSpan {
 pub struct Note {
     location: FileLocation,
     spans: Vec<NoteSpan>,
+    attributes: HashMap<String, String>,
+    priority: i64,
+    lines: usize,
 }
 
 impl Note {
     pub fn new(location: FileLocation, spans: Vec<NoteSpan>) -> Note {
-        Note { location, spans }
+        Note {
+            location,
+            spans,
+            attributes: Default::default(),
+            priority: 0,
+            lines: 1,
+        }
+    }
+
+    pub fn with_attributes(mut self, attributes: HashMap<String, String>) -> Note {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i64) -> Note {
+        self.priority = priority;
+        self
+    }
+
+    /*@[Core/Model/Note]:
+    A note spans the lines of the comment it was collected from, starting at the location line.
+    The comment markers count too, so a block comment with the body starting on the next line spans
+    more lines than its text.
+    */
+    pub fn with_lines(mut self, lines: usize) -> Note {
+        self.lines = lines.max(1);
+        self
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
     }
 
     pub fn spans(&self) -> &Vec<NoteSpan> {
         &self.spans
     }
 
+    pub fn spans_mut(&mut self) -> &mut Vec<NoteSpan> {
+        &mut self.spans
+    }
+
+    pub fn attributes(&self) -> &HashMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.attributes
+    }
+
+    pub fn priority(&self) -> i64 {
+        self.priority
+    }
+
     pub fn location(&self) -> &FileLocation {
         &self.location
     }
@@ -2,13 +2,14 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 
 use anyhow::Result;
 
+use crate::model::attributes;
 use crate::model::file_location::FileLocation;
 use crate::model::note::NoteSpan;
 
 use super::handle::*;
 use super::note::Note;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node {
     handle: Handle,
     attributes: HashMap<String, String>,
@@ -45,6 +46,26 @@ impl Node {
         &self.children
     }
 
+    /*@[Core/Model/Tree]:
+    Children are stored sorted by their handle parts, which is the default order for rendering.
+    Nodes with `order` attribute (an integer) are placed before the rest of their siblings, in the
+    ascending order of the attribute values.
+    */
+    pub fn sorted_children(&self) -> Vec<&Node> {
+        let mut children: Vec<&Node> = self.children.values().map(|n| n.as_ref()).collect();
+        children.sort_by_key(|n| match n.order() {
+            Some(o) => (0, o),
+            None => (1, 0),
+        });
+        children
+    }
+
+    fn order(&self) -> Option<i64> {
+        self.attributes
+            .get(attributes::ORDER)
+            .and_then(|o| o.trim().parse().ok())
+    }
+
     pub fn attributes(&self) -> &HashMap<String, String> {
         &self.attributes
     }
@@ -118,7 +139,17 @@ impl Node {
         if note.spans().is_empty() {
             node.extra.push(note.location().clone());
         } else {
-            node.notes.push(note);
+            /*@[Core/Model/Tree]:
+            Notes are kept in the order of collection (which follows the order of the scanned files),
+            except for the notes with a higher priority, which are placed before the notes with a lower one.
+            This allows pinning a "summary" note of a concept first regardless of the file it lives in.
+            */
+            let index = node
+                .notes
+                .iter()
+                .position(|n| n.priority() < note.priority())
+                .unwrap_or(node.notes.len());
+            node.notes.insert(index, note);
         }
     }
 
@@ -130,12 +161,111 @@ impl Node {
         }
     }
 
-    fn register_mention(&mut self, to: &Handle, from: &Handle) {
+    pub(crate) fn register_mention(&mut self, to: &Handle, from: &Handle) {
         let node = self.find_node_mut(to);
 
         node.mentions.insert(from.clone());
     }
 
+    /*@[Core/Model/Tree]:
+    Pruning removes the nodes (along with their subtrees) and the notes not satisfying the predicates.
+    The tree has to stay consistent afterwards, so the mentions are rebuilt from the remaining notes,
+    and the links to the removed nodes are turned into plain text (using the node titles).
+    The root node is never removed.
+    */
+    pub fn prune<N, M>(&mut self, keep_node: &N, keep_note: &M)
+    where
+        N: Fn(&Node) -> bool,
+        M: Fn(&Node, &Note) -> bool,
+    {
+        let mut titles = HashMap::new();
+        self.collect_titles(&mut titles);
+
+        self.prune_recursive(keep_node, keep_note);
+
+        let mut existing = HashSet::new();
+        self.collect_handles(&mut existing);
+
+        self.unlink_missing(&existing, &titles);
+
+        let mut links = vec![];
+        self.collect_links(&mut links);
+
+        for (to, from) in links {
+            self.register_mention(&to, &from);
+        }
+    }
+
+    fn collect_titles(&self, titles: &mut HashMap<Handle, String>) {
+        let title = self
+            .attributes
+            .get(attributes::TITLE)
+            .cloned()
+            .or_else(|| self.handle.parts().last().cloned())
+            .unwrap_or_default();
+
+        titles.insert(self.handle.clone(), title);
+
+        for n in self.children.values() {
+            n.collect_titles(titles);
+        }
+    }
+
+    fn collect_handles(&self, handles: &mut HashSet<Handle>) {
+        handles.insert(self.handle.clone());
+
+        for n in self.children.values() {
+            n.collect_handles(handles);
+        }
+    }
+
+    fn prune_recursive<N, M>(&mut self, keep_node: &N, keep_note: &M)
+    where
+        N: Fn(&Node) -> bool,
+        M: Fn(&Node, &Note) -> bool,
+    {
+        let notes = std::mem::take(&mut self.notes);
+        self.notes = notes.into_iter().filter(|n| keep_note(self, n)).collect();
+
+        self.children.retain(|_, n| keep_node(n));
+
+        for n in self.children.values_mut() {
+            n.prune_recursive(keep_node, keep_note);
+        }
+    }
+
+    fn unlink_missing(&mut self, existing: &HashSet<Handle>, titles: &HashMap<Handle, String>) {
+        self.mentions.clear();
+
+        for n in &mut self.notes {
+            for s in n.spans_mut() {
+                if let NoteSpan::Link(h) = s {
+                    if !existing.contains(h) {
+                        *s = NoteSpan::Text(titles.get(h).cloned().unwrap_or(h.to_string()));
+                    }
+                }
+            }
+        }
+
+        for n in self.children.values_mut() {
+            n.unlink_missing(existing, titles);
+        }
+    }
+
+    fn collect_links(&self, links: &mut Vec<(Handle, Handle)>) {
+        for n in &self.notes {
+            for s in n.spans() {
+                if let NoteSpan::Link(h) = s {
+                    links.push((h.clone(), self.handle.clone()));
+                }
+            }
+        }
+
+        for n in self.children.values() {
+            n.collect_links(links);
+        }
+    }
+
     #[allow(dead_code)]
     pub fn visit<F>(&self, f: &F) -> Result<()>
     where
@@ -316,4 +446,61 @@ mod test {
             visited.into_inner()
         );
     }
+
+    #[test]
+    fn sorting_children() {
+        let mut kt = Node::root();
+
+        for (h, order) in [
+            ("a", None),
+            ("b", Some("2")),
+            ("c", None),
+            ("d", Some("-1")),
+        ] {
+            let handle = Handle::from_str(h).unwrap();
+            kt.find_node_mut(&handle);
+            if let Some(o) = order {
+                kt.merge_attributes(
+                    &handle,
+                    HashMap::from([(attributes::ORDER.to_string(), o.to_string())]),
+                );
+            }
+        }
+
+        assert_eq!(
+            vec!("d", "b", "a", "c"),
+            kt.sorted_children()
+                .iter()
+                .map(|n| n.handle.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ordering_notes_by_priority() {
+        let mut kt = Node::root();
+        let handle = Handle::from_str("a").unwrap();
+
+        let note = |line: usize, priority: i64| {
+            Note::new(
+                FileLocation::new_relative("file.go", line),
+                vec![NoteSpan::Text("Note".to_string())],
+            )
+            .with_priority(priority)
+        };
+
+        for (line, priority) in [(1, 0), (2, 0), (3, 10), (4, -1), (5, 10), (6, 0)] {
+            kt.add_note(&handle, note(line, priority));
+        }
+
+        assert_eq!(
+            vec!(3, 5, 1, 2, 6, 4),
+            kt.find_node(&handle)
+                .unwrap()
+                .notes
+                .iter()
+                .map(|n| n.location().line())
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -37,14 +37,17 @@ mod tests {
             Quote {
                 body: "Block comment\nis long".to_string(),
                 line: 4,
+                lines: 4,
             },
             Quote {
                 body: "Inline comment".to_string(),
                 line: 10,
+                lines: 1,
             },
             Quote {
                 body: "Another inline comment".to_string(),
                 line: 11,
+                lines: 1,
             },
         ];
 
@@ -12,6 +12,8 @@ pub mod rust;
 pub struct Quote {
     pub body: String,
     pub line: usize,
+    /// Number of lines the whole comment spans, including the comment markers.
+    pub lines: usize,
 }
 
 pub trait FileParser {
@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::model::attributes;
+use crate::model::handle::{slugify, Handle};
+use crate::model::tree::Node;
+use crate::renderer::resolve_numbered_title;
+
+pub const TOC_TITLE: &str = "Table of contents";
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum AnchorStyle {
+    Handle,
+    GitHub,
+}
+
+impl FromStr for AnchorStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<AnchorStyle> {
+        match s.to_lowercase().as_str() {
+            "handle" => Ok(AnchorStyle::Handle),
+            "github" | "gitlab" => Ok(AnchorStyle::GitHub),
+            _ => Err(anyhow!("Unknown anchor style `{}`", s)),
+        }
+    }
+}
+
+/*@[Core/Renderer/Anchors]:
+Anchors are resolved for the whole tree at once, in the same order the nodes are rendered, so the
+collisions can be detected and resolved the same way GitHub does it: by adding `-1`, `-2`, etc. suffixes
+to the repeated slugs.
+
+There are two styles of anchors:
+- `Handle` (default) - derived from the node handles. These are stable (don't change when the titles
+are changed) but require emitting explicit `<a id>` tags.
+- `GitHub` - derived from the heading text following GitHub/GitLab rules. This way the anchors
+generated by the code hosting platform itself can be used. Headings not associated with the nodes
+(like TOC) have to be accounted for too, as they take part in the numbering.
+*/
+pub struct Anchors {
+    style: AnchorStyle,
+    anchors: HashMap<Handle, String>,
+}
+
+impl Anchors {
+    pub fn build(root: &Node, style: AnchorStyle) -> Anchors {
+        let mut anchors = Anchors {
+            style,
+            anchors: Default::default(),
+        };
+
+        let mut used = HashSet::new();
+        anchors.visit(root, root, &mut used);
+
+        anchors
+    }
+
+    pub fn style(&self) -> AnchorStyle {
+        self.style
+    }
+
+    pub fn get(&self, handle: &Handle) -> String {
+        self.anchors
+            .get(handle)
+            .cloned()
+            .unwrap_or_else(|| handle.as_url_safe_string())
+    }
+
+    fn visit(&mut self, root: &Node, node: &Node, used: &mut HashSet<String>) {
+        let slug = match self.style {
+            AnchorStyle::Handle => node.handle().as_url_safe_string(),
+            AnchorStyle::GitHub => slugify(&resolve_numbered_title(root, node.handle())),
+        };
+
+        self.anchors
+            .insert(node.handle().clone(), Self::reserve(slug, used));
+
+        if self.style == AnchorStyle::GitHub
+            && node.attributes().get(attributes::TOC).map(|v| v.as_str()) == Some("true")
+        {
+            Self::reserve(slugify(TOC_TITLE), used);
+        }
+
+        for n in node.sorted_children() {
+            self.visit(root, n, used);
+        }
+    }
+
+    pub(crate) fn reserve(slug: String, used: &mut HashSet<String>) -> String {
+        let mut candidate = slug.clone();
+        let mut i = 0;
+
+        while used.contains(&candidate) {
+            i += 1;
+            candidate = format!("{}-{}", slug, i);
+        }
+
+        used.insert(candidate.clone());
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn build_tree() -> Node {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::TITLE.to_string(), "Notes".to_string()),
+                (attributes::TOC.to_string(), "true".to_string()),
+            ]),
+        );
+
+        for h in ["C#", "C", "C/Table of contents", "C-1", "Notes"] {
+            tree.find_node_mut(&Handle::from_str(h).unwrap());
+        }
+
+        tree
+    }
+
+    #[test]
+    fn resolve_collisions_for_handles() {
+        let anchors = Anchors::build(&build_tree(), AnchorStyle::Handle);
+        let get = |h: &str| anchors.get(&Handle::from_str(h).unwrap());
+
+        assert_eq!("", anchors.get(&Handle::ROOT));
+        assert_eq!("c", get("C"));
+        assert_eq!("c+table-of-contents", get("C/Table of contents"));
+        assert_eq!("c-1", get("C#"));
+        assert_eq!("c-1-1", get("C-1"));
+        assert_eq!("notes", get("Notes"));
+    }
+
+    #[test]
+    fn resolve_collisions_for_github() {
+        let anchors = Anchors::build(&build_tree(), AnchorStyle::GitHub);
+        let get = |h: &str| anchors.get(&Handle::from_str(h).unwrap());
+
+        assert_eq!("notes", anchors.get(&Handle::ROOT));
+        assert_eq!("c", get("C"));
+        assert_eq!("table-of-contents-1", get("C/Table of contents"));
+        assert_eq!("c-1", get("C#"));
+        assert_eq!("c-1-1", get("C-1"));
+        assert_eq!("notes-1", get("Notes"));
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+use crate::renderer::staging::StagingArea;
+use crate::renderer::{resolve_node_title, Renderer};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum GraphFormat {
+    Mermaid,
+    Dot,
+}
+
+impl FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<GraphFormat> {
+        match s.to_lowercase().as_str() {
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            _ => Err(anyhow!("Unknown graph format `{}`", s)),
+        }
+    }
+}
+
+/*@[Core/Renderer/Graph]:
+Graph renderer draws the hierarchy of the nodes (solid edges) along with the mentions (dashed edges,
+from the mentioning node to the mentioned one) as a concept map.
+
+Big trees produce unreadable graphs, so the graph can be limited to a subtree (`prefix`) and to a
+certain `depth` below it. Mentions coming from or pointing to the nodes hidden by the depth limit are
+attributed to their closest visible ancestors instead of being dropped.
+*/
+pub struct GraphRenderer {
+    path: PathBuf,
+    format: GraphFormat,
+    prefix: Handle,
+    depth: Option<usize>,
+}
+
+struct GraphNode {
+    handle: Handle,
+    title: String,
+}
+
+impl GraphRenderer {
+    pub fn new<P: Into<PathBuf>>(
+        path: P,
+        format: GraphFormat,
+        prefix: Option<Handle>,
+        depth: Option<usize>,
+    ) -> GraphRenderer {
+        GraphRenderer {
+            path: path.into(),
+            format,
+            prefix: prefix.unwrap_or(Handle::ROOT),
+            depth,
+        }
+    }
+
+    fn max_depth(&self) -> usize {
+        self.depth
+            .map(|d| self.prefix.parts().len() + d)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn collect_nodes(&self, root: &Node, node: &Node, nodes: &mut Vec<GraphNode>) {
+        if node.handle().parts().len() > self.max_depth() {
+            return;
+        }
+
+        if !node.handle().parts().is_empty() {
+            nodes.push(GraphNode {
+                handle: node.handle().clone(),
+                title: resolve_node_title(root, node.handle()),
+            });
+        }
+
+        for n in node.sorted_children() {
+            self.collect_nodes(root, n, nodes);
+        }
+    }
+
+    fn visible(&self, handle: &Handle) -> Option<Handle> {
+        let parts = handle.parts();
+
+        if parts.len() < self.prefix.parts().len() || !parts.starts_with(self.prefix.parts()) {
+            return None;
+        }
+
+        Handle::from_parts(parts[..parts.len().min(self.max_depth())].to_vec()).ok()
+    }
+
+    fn collect_mentions(&self, node: &Node, edges: &mut BTreeSet<(Vec<String>, Vec<String>)>) {
+        if let Some(to) = self.visible(node.handle()) {
+            for m in node.mentions() {
+                match self.visible(m) {
+                    Some(from) if from != to => {
+                        edges.insert((from.parts().clone(), to.parts().clone()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for n in node.children().values() {
+            self.collect_mentions(n, edges);
+        }
+    }
+
+    fn render_mermaid(
+        &self,
+        nodes: &[GraphNode],
+        mentions: &BTreeSet<(Vec<String>, Vec<String>)>,
+    ) -> Result<String> {
+        let ids: HashMap<&[String], String> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.handle.parts().as_slice(), format!("n{}", i)))
+            .collect();
+
+        let fenced = self
+            .path
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("md"))
+            .unwrap_or(false);
+
+        let mut out = String::new();
+
+        if fenced {
+            out.write_str("```mermaid\n")?;
+        }
+
+        out.write_str("flowchart LR\n")?;
+
+        for n in nodes {
+            writeln!(
+                out,
+                "    {}[\"{}\"]",
+                ids[n.handle.parts().as_slice()],
+                n.title.replace('"', "#quot;")
+            )?;
+        }
+
+        for n in nodes {
+            if let Some(parent) = ids.get(&n.handle.parts()[..n.handle.parts().len() - 1]) {
+                writeln!(
+                    out,
+                    "    {} --> {}",
+                    parent,
+                    ids[n.handle.parts().as_slice()]
+                )?;
+            }
+        }
+
+        for (from, to) in mentions {
+            writeln!(
+                out,
+                "    {} -.-> {}",
+                ids[from.as_slice()],
+                ids[to.as_slice()]
+            )?;
+        }
+
+        if fenced {
+            out.write_str("```\n")?;
+        }
+
+        Ok(out)
+    }
+
+    fn render_dot(
+        &self,
+        nodes: &[GraphNode],
+        mentions: &BTreeSet<(Vec<String>, Vec<String>)>,
+    ) -> Result<String> {
+        let id = |parts: &[String]| format!("\"{}\"", parts.join("/").replace('"', "\\\""));
+        let included: HashSet<&[String]> =
+            nodes.iter().map(|n| n.handle.parts().as_slice()).collect();
+
+        let mut out = String::from("digraph memorial {\n    rankdir=LR;\n");
+
+        for n in nodes {
+            writeln!(
+                out,
+                "    {} [label=\"{}\"];",
+                id(n.handle.parts()),
+                n.title.replace('"', "\\\"")
+            )?;
+        }
+
+        for n in nodes {
+            let parts = n.handle.parts();
+            let parent = &parts[..parts.len() - 1];
+            if included.contains(parent) {
+                writeln!(out, "    {} -> {};", id(parent), id(parts))?;
+            }
+        }
+
+        for (from, to) in mentions {
+            writeln!(out, "    {} -> {} [style=dashed];", id(from), id(to))?;
+        }
+
+        out.write_str("}\n")?;
+
+        Ok(out)
+    }
+}
+
+impl Renderer for GraphRenderer {
+    fn render(&self, root: &Node, fs: &mut StagingArea) -> Result<()> {
+        let start = match root.find_node(&self.prefix) {
+            Some(n) => n,
+            None => return Err(anyhow!("Graph prefix `{}` not found", self.prefix)),
+        };
+
+        let mut nodes = vec![];
+        self.collect_nodes(root, start, &mut nodes);
+
+        let mut mentions = BTreeSet::new();
+        self.collect_mentions(start, &mut mentions);
+
+        let rendered = match self.format {
+            GraphFormat::Mermaid => self.render_mermaid(&nodes, &mentions)?,
+            GraphFormat::Dot => self.render_dot(&nodes, &mentions)?,
+        };
+
+        fs.open_as_new(&self.path)?.write_str(&rendered)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn build_tree() -> Node {
+        let mut tree = Node::root();
+
+        tree.add_note(
+            &Handle::from_str("a/b/c").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("note 1".to_string()),
+                    NoteSpan::Link(Handle::from_str("a/d").unwrap()),
+                ],
+            ),
+        );
+
+        tree.add_note(
+            &Handle::from_str("x").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file2.ext", 234),
+                vec![NoteSpan::Link(Handle::from_str("a/d").unwrap())],
+            ),
+        );
+
+        tree
+    }
+
+    fn render(renderer: GraphRenderer) -> String {
+        let mut fs = StagingArea::new().unwrap();
+        renderer.render(&build_tree(), &mut fs).unwrap();
+        String::from_utf8(fs.open(&renderer.path).unwrap().contents().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn render_mermaid() {
+        let generated = render(GraphRenderer::new(
+            "graph.md",
+            GraphFormat::Mermaid,
+            None,
+            None,
+        ));
+
+        let expected = r#"```mermaid
+flowchart LR
+    n0["a"]
+    n1["b"]
+    n2["c"]
+    n3["d"]
+    n4["x"]
+    n0 --> n1
+    n1 --> n2
+    n0 --> n3
+    n2 -.-> n3
+    n4 -.-> n3
+```
+"#;
+
+        assert_eq!(expected, generated);
+    }
+
+    #[test]
+    fn render_dot_with_prefix_and_depth() {
+        let generated = render(GraphRenderer::new(
+            "graph.dot",
+            GraphFormat::Dot,
+            Some(Handle::from_str("a").unwrap()),
+            Some(1),
+        ));
+
+        let expected = r#"digraph memorial {
+    rankdir=LR;
+    "a" [label="a"];
+    "a/b" [label="b"];
+    "a/d" [label="d"];
+    "a" -> "a/b";
+    "a" -> "a/d";
+    "a/b" -> "a/d" [style=dashed];
+}
+"#;
+
+        assert_eq!(expected, generated);
+    }
+}
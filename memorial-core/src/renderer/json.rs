@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use anyhow::Result;
+use serde_derive::Serialize;
+
+use crate::model::attributes;
+use crate::model::file_location::FileLocation;
+use crate::model::handle::Handle;
+use crate::model::note::{Note, NoteSpan};
+use crate::model::tree::Node;
+use crate::renderer::staging::StagingArea;
+use crate::renderer::Renderer;
+
+/*@[Core/Renderer/JSON]:
+JSON output is meant for machines (dashboards, bots, linters) rather than humans. It exposes the same
+knowledge tree the other renderers see, but without any formatting decisions applied.
+
+The structure of the document is a contract with external tools, so it's versioned separately from
+the application. `SCHEMA_VERSION` should be bumped on any breaking change (removing or renaming fields,
+changing types or semantics). Adding new fields is not considered a breaking change.
+*/
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub struct JsonRenderer {
+    path: String,
+}
+
+impl JsonRenderer {
+    pub fn new(path: String) -> JsonRenderer {
+        JsonRenderer { path }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&self, root: &Node, fs: &mut StagingArea) -> Result<()> {
+        let document = JsonDocument {
+            schema_version: SCHEMA_VERSION,
+            root: JsonNode::from_node(root),
+        };
+
+        let out = fs.open_as_new(&self.path)?;
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        out.write_all(b"\n")?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    schema_version: u32,
+    root: JsonNode<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    handle: &'a [String],
+    title: &'a str,
+    attributes: BTreeMap<&'a str, &'a str>,
+    notes: Vec<JsonNote<'a>>,
+    extra: Vec<JsonLocation<'a>>,
+    mentions: Vec<&'a [String]>,
+    children: Vec<JsonNode<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonNote<'a> {
+    location: JsonLocation<'a>,
+    attributes: BTreeMap<&'a str, &'a str>,
+    spans: Vec<JsonSpan<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonSpan<'a> {
+    Text { text: &'a str },
+    Link { handle: &'a [String] },
+}
+
+#[derive(Serialize)]
+struct JsonLocation<'a> {
+    title: &'a str,
+    path: String,
+    line: usize,
+    relative: bool,
+}
+
+impl<'a> JsonNode<'a> {
+    fn from_node(node: &'a Node) -> JsonNode<'a> {
+        let mut mentions: Vec<&Handle> = node.mentions().iter().collect();
+        mentions.sort_by(|a, b| a.parts().cmp(b.parts()));
+
+        JsonNode {
+            handle: node.handle().parts(),
+            title: node
+                .attributes()
+                .get(attributes::TITLE)
+                .map(|s| s.as_str())
+                .or_else(|| node.handle().parts().last().map(|s| s.as_str()))
+                .unwrap_or(""),
+            attributes: node
+                .attributes()
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect(),
+            notes: node.notes().iter().map(JsonNote::from_note).collect(),
+            extra: node
+                .extra()
+                .iter()
+                .map(JsonLocation::from_location)
+                .collect(),
+            mentions: mentions.into_iter().map(|h| h.parts().as_slice()).collect(),
+            children: node
+                .sorted_children()
+                .into_iter()
+                .map(JsonNode::from_node)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> JsonNote<'a> {
+    fn from_note(note: &'a Note) -> JsonNote<'a> {
+        JsonNote {
+            location: JsonLocation::from_location(note.location()),
+            attributes: note
+                .attributes()
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect(),
+            spans: note
+                .spans()
+                .iter()
+                .map(|s| match s {
+                    NoteSpan::Text(text) => JsonSpan::Text { text },
+                    NoteSpan::Link(handle) => JsonSpan::Link {
+                        handle: handle.parts(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> JsonLocation<'a> {
+    fn from_location(location: &'a FileLocation) -> JsonLocation<'a> {
+        JsonLocation {
+            title: location.title(),
+            path: location.path().to_string(),
+            line: location.line(),
+            relative: location.is_relative(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn render_from_tree() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([(attributes::TITLE.to_string(), "Big Nice Title".to_string())]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a/b").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("note 1".to_string()),
+                    NoteSpan::Link(Handle::from_str("a/c").unwrap()),
+                ],
+            )
+            .with_attributes(HashMap::from([(
+                attributes::SINCE.to_string(),
+                "2.3".to_string(),
+            )])),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a/c").unwrap(),
+            Note::new(FileLocation::new_relative("path/to/file2.ext", 234), vec![]),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        let renderer = JsonRenderer::new("test.json".to_string());
+
+        renderer.render(&tree, &mut fs).unwrap();
+
+        let generated: serde_json::Value =
+            serde_json::from_slice(&fs.open("test.json").unwrap().contents().unwrap()).unwrap();
+
+        let expected = json!({
+            "schema_version": 1,
+            "root": {
+                "handle": [],
+                "title": "Big Nice Title",
+                "attributes": { "title": "Big Nice Title" },
+                "notes": [],
+                "extra": [],
+                "mentions": [],
+                "children": [{
+                    "handle": ["a"],
+                    "title": "a",
+                    "attributes": {},
+                    "notes": [],
+                    "extra": [],
+                    "mentions": [],
+                    "children": [{
+                        "handle": ["a", "b"],
+                        "title": "b",
+                        "attributes": {},
+                        "notes": [{
+                            "location": {
+                                "title": "path/to/file1.ext",
+                                "path": "path/to/file1.ext",
+                                "line": 123,
+                                "relative": true
+                            },
+                            "attributes": { "since": "2.3" },
+                            "spans": [
+                                { "type": "text", "text": "note 1" },
+                                { "type": "link", "handle": ["a", "c"] }
+                            ]
+                        }],
+                        "extra": [],
+                        "mentions": [],
+                        "children": []
+                    }, {
+                        "handle": ["a", "c"],
+                        "title": "c",
+                        "attributes": {},
+                        "notes": [],
+                        "extra": [{
+                            "title": "path/to/file2.ext",
+                            "path": "path/to/file2.ext",
+                            "line": 234,
+                            "relative": true
+                        }],
+                        "mentions": [["a", "b"]],
+                        "children": []
+                    }]
+                }]
+            }
+        });
+
+        assert_eq!(expected, generated);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::Write;
 
 use anyhow::Result;
@@ -7,14 +8,17 @@ use crate::model::file_location::FileLocation;
 use crate::model::handle::Handle;
 use crate::model::note::{Note, NoteSpan};
 use crate::model::tree::Node;
+use crate::renderer::anchors::{AnchorStyle, Anchors, TOC_TITLE};
 use crate::renderer::staging::{StagedFile, StagingArea};
-use crate::renderer::Renderer;
+use crate::renderer::{resolve_numbered_title, Renderer};
 
 pub struct MarkdownRenderer {}
 
-struct RendererSession<'a> {
+pub(crate) struct RendererSession<'a> {
     root: &'a Node,
     out: &'a mut StagedFile,
+    anchors: &'a Anchors,
+    chapters: Option<&'a HashMap<Handle, String>>,
 }
 
 impl MarkdownRenderer {
@@ -30,10 +34,19 @@ impl Renderer for MarkdownRenderer {
         This can be user-controlled by using attributes. Hence that's how output file path is
         passed to the renderer. But, for now, only a value from the root node is used.
         */
-        RendererSession {
+        let anchor_style = match root.attributes().get(attributes::ANCHORS) {
+            Some(s) => s.parse()?,
+            None => AnchorStyle::Handle,
+        };
+
+        let anchors = Anchors::build(root, anchor_style);
+
+        RendererSession::new(
             root,
-            out: fs.open_as_new(root.attributes().get(attributes::OUTPUT_FILE_NAME).unwrap()),
-        }
+            fs.open_as_new(root.attributes().get(attributes::OUTPUT_FILE_NAME).unwrap())?,
+            &anchors,
+            None,
+        )
         .render()
     }
 }
@@ -45,6 +58,24 @@ preparing the data in a certain way, which, depending on the engine implementati
 features exposed from the `Renderer`.
 */
 impl<'a> RendererSession<'a> {
+    /*@[Core/Renderer/Markdown]:
+    The same session is used for rendering multi-file outputs (like mdBook chapters). In this case
+    `chapters` maps handles to the files the nodes are rendered into, so the links can point to other files.
+    */
+    pub(crate) fn new(
+        root: &'a Node,
+        out: &'a mut StagedFile,
+        anchors: &'a Anchors,
+        chapters: Option<&'a HashMap<Handle, String>>,
+    ) -> RendererSession<'a> {
+        RendererSession {
+            root,
+            out,
+            anchors,
+            chapters,
+        }
+    }
+
     fn render(mut self) -> Result<()> {
         self.render_node(1, self.root)?;
 
@@ -58,7 +89,7 @@ impl<'a> RendererSession<'a> {
         Ok(())
     }
 
-    fn render_footer(&mut self) -> Result<()> {
+    pub(crate) fn render_footer(&mut self) -> Result<()> {
         self.w(&*format!(
             "\n---\n<sub>Generated by [Memorial](https://github.com/Kostassoid/memorial) v{} at _{}_.</sub>",
             &self.root.attributes().get(attributes::APP_VERSION).unwrap_or(&"?".to_string()),
@@ -66,13 +97,34 @@ impl<'a> RendererSession<'a> {
         ))
     }
 
-    fn render_node(&mut self, level: usize, node: &Node) -> Result<()> {
-        self.w(&*format!(
-            "{} <a id=\"{}\"></a> {}\n\n",
-            "#".repeat(level),
-            node.handle().as_url_safe_string(),
-            self.resolve_node_title(node.handle()),
-        ))?;
+    pub(crate) fn render_node(&mut self, level: usize, node: &Node) -> Result<()> {
+        self.render_node_body(level, node)?;
+
+        for n in node.sorted_children() {
+            self.render_node(level + 1, n)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn render_node_body(&mut self, level: usize, node: &Node) -> Result<()> {
+        match self.anchors.style() {
+            AnchorStyle::Handle => self.w(&*format!(
+                "{} <a id=\"{}\"></a> {}\n\n",
+                "#".repeat(level),
+                self.anchors.get(node.handle()),
+                self.resolve_node_title(node.handle()),
+            ))?,
+            AnchorStyle::GitHub => self.w(&*format!(
+                "{} {}\n\n",
+                "#".repeat(level),
+                self.resolve_node_title(node.handle()),
+            ))?,
+        }
+
+        if let Some(owners) = node.attributes().get(attributes::OWNERS) {
+            self.w(&*format!("_Owners:_ {}\n\n", owners))?;
+        }
 
         self.render_toc(level + 1, node)?;
 
@@ -94,10 +146,6 @@ impl<'a> RendererSession<'a> {
             }
         }
 
-        for (_, n) in node.children() {
-            self.render_node(level + 1, n)?;
-        }
-
         Ok(())
     }
 
@@ -111,7 +159,7 @@ impl<'a> RendererSession<'a> {
             return Ok(());
         }
 
-        self.w(&*format!("{} Table of contents\n\n", "#".repeat(level),))?;
+        self.w(&*format!("{} {}\n\n", "#".repeat(level), TOC_TITLE))?;
 
         self.render_toc_links(0, node)?;
 
@@ -129,28 +177,47 @@ impl<'a> RendererSession<'a> {
             ))?;
         }
 
-        for (_, n) in node.children() {
+        for n in node.sorted_children() {
             self.render_toc_links(level + 1, n)?;
         }
 
         Ok(())
     }
 
+    /*@[Core/Renderer/Markdown]:
+    Note bodies are Markdown on their own, so the spans are joined as is, without adding any separators.
+    This keeps fenced code blocks, tables and lists intact, and allows punctuation right after the links.
+    When rendered as blockquotes (default), each line of the body is prefixed separately.
+    */
     fn format_note(&self, note: &Note) -> String {
-        let mut formatted = String::from("> ");
+        let mut body = String::new();
 
         for s in note.spans() {
             match s {
-                NoteSpan::Text(s) => formatted
-                    .write_str(&format!("{} ", s.replace("\n", "\n> ")))
-                    .unwrap(),
-                NoteSpan::Link(handle) => formatted.write_str(&self.format_link(handle)).unwrap(),
+                NoteSpan::Text(s) => body.push_str(s),
+                NoteSpan::Link(handle) => body.push_str(&self.format_link(handle)),
             }
         }
 
+        let mut formatted = if self.quote_notes() {
+            body.lines()
+                .map(|l| match l {
+                    "" => ">".to_string(),
+                    _ => format!("> {}", l),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            body
+        };
+
         let l = note.location();
         formatted
-            .write_str(&format!("\n\nat {}\n", Self::format_location(l)))
+            .write_str(&format!(
+                "\n\nat {}{}\n\n",
+                Self::format_location(l).trim_end(),
+                self.format_blame(note)
+            ))
             .unwrap();
 
         formatted.write_str("\n\n").unwrap();
@@ -158,31 +225,65 @@ impl<'a> RendererSession<'a> {
         formatted
     }
 
+    fn format_blame(&self, note: &Note) -> String {
+        let show = self
+            .root
+            .attributes()
+            .get(attributes::SHOW_BLAME)
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let a = note.attributes();
+        match (show, a.get(attributes::AUTHOR), a.get(attributes::MODIFIED)) {
+            (true, Some(author), Some(modified)) => format!(
+                " by {} on {}{}",
+                author,
+                modified,
+                if a.contains_key(attributes::STALE) {
+                    " _(stale)_"
+                } else {
+                    ""
+                }
+            ),
+            _ => String::new(),
+        }
+    }
+
+    fn quote_notes(&self) -> bool {
+        self.root
+            .attributes()
+            .get(attributes::QUOTE_NOTES)
+            .map(|v| v != "false")
+            .unwrap_or(true)
+    }
+
     fn format_location(l: &FileLocation) -> String {
         format!("[{} (line {})]({})\n", l.title(), l.line(), l.path())
     }
 
     fn format_link(&self, h: &Handle) -> String {
         format!(
-            "[{}](#{}) ",
+            "[{}]({}#{})",
             self.resolve_node_title(h),
-            h.as_url_safe_string(),
+            self.resolve_chapter(h).unwrap_or(""),
+            self.anchors.get(h),
         )
     }
 
+    fn resolve_chapter(&self, h: &Handle) -> Option<&'a str> {
+        let chapters = self.chapters?;
+
+        (0..=h.parts().len()).rev().find_map(|i| {
+            let parent = match i {
+                0 => Handle::ROOT,
+                _ => Handle::from_parts(h.parts()[..i].to_vec()).ok()?,
+            };
+            chapters.get(&parent).map(|c| c.as_str())
+        })
+    }
+
     fn resolve_node_title(&self, handle: &Handle) -> String {
-        self.root
-            .find_node(handle)
-            .map(|n| n.attributes().get(attributes::TITLE))
-            .flatten()
-            .map(|s| s.to_string())
-            .unwrap_or(
-                handle
-                    .parts()
-                    .last()
-                    .unwrap_or(&String::from("(root)"))
-                    .to_string(),
-            )
+        resolve_numbered_title(self.root, handle)
     }
 }
 
@@ -190,6 +291,8 @@ impl<'a> RendererSession<'a> {
 mod test {
     use std::collections::HashMap;
 
+    use crate::decorators::numbering::NumberingDecorator;
+    use crate::decorators::Decorator;
     use crate::model::handle::Handle;
 
     use super::*;
@@ -217,8 +320,9 @@ mod test {
             Note::new(
                 FileLocation::new_relative("path/to/file1.ext", 123),
                 vec![
-                    NoteSpan::Text("note 1".to_string()),
+                    NoteSpan::Text("note 1, see ".to_string()),
                     NoteSpan::Link(Handle::from_str("a/b/d").unwrap()),
+                    NoteSpan::Text(".\n\n```\ncode\n```".to_string()),
                 ],
             ),
         );
@@ -236,12 +340,12 @@ mod test {
             ),
         );
 
-        let mut fs = StagingArea::new();
+        let mut fs = StagingArea::new().unwrap();
         let renderer = MarkdownRenderer::new();
 
         renderer.render(&tree, &mut fs).unwrap();
 
-        let generated = String::from_utf8(fs.open("test").unwrap().contents().clone())
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap())
             .unwrap()
             .replace(" \n", "\n");
 
@@ -261,7 +365,11 @@ mod test {
 
 #### <a id="a+b+c"></a> Sub title
 
-> note 1 [d](#a+b+d)
+> note 1, see [d](#a+b+d).
+>
+> ```
+> code
+> ```
 
 at [path/to/file1.ext (line 123)](path/to/file1.ext)
 
@@ -285,4 +393,173 @@ _Mentioned in:_
 
         assert_eq!(expected, generated);
     }
+
+    #[test]
+    fn render_notes_without_quotes() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::OUTPUT_FILE_NAME.to_string(), "test".to_string()),
+                (attributes::QUOTE_NOTES.to_string(), "false".to_string()),
+            ]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("| x | y |\n|---|---|\n| 1 | ".to_string()),
+                    NoteSpan::Link(Handle::from_str("a").unwrap()),
+                    NoteSpan::Text(" |".to_string()),
+                ],
+            ),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        MarkdownRenderer::new().render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        assert!(generated.contains(
+            "\n\n| x | y |\n|---|---|\n| 1 | [a](#a) |\n\nat [path/to/file1.ext (line 123)]"
+        ));
+    }
+
+    #[test]
+    fn render_with_github_anchors() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::TITLE.to_string(), "Notes".to_string()),
+                (attributes::OUTPUT_FILE_NAME.to_string(), "test".to_string()),
+                (attributes::TOC.to_string(), "true".to_string()),
+                (attributes::ANCHORS.to_string(), "github".to_string()),
+            ]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("C#").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![NoteSpan::Link(Handle::from_str("C").unwrap())],
+            ),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        MarkdownRenderer::new().render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        assert!(generated
+            .starts_with("# Notes\n\n## Table of contents\n\n- [C](#c)\n- [C#](#c-1)\n\n## C\n\n"));
+        assert!(generated.contains("\n## C#\n\n> [C](#c)\n"));
+    }
+
+    #[test]
+    fn render_blame_metadata() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::OUTPUT_FILE_NAME.to_string(), "test".to_string()),
+                (attributes::SHOW_BLAME.to_string(), "true".to_string()),
+            ]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![NoteSpan::Text("note 1".to_string())],
+            )
+            .with_attributes(HashMap::from([
+                (attributes::AUTHOR.to_string(), "Alice".to_string()),
+                (attributes::MODIFIED.to_string(), "2023-11-14".to_string()),
+                (attributes::STALE.to_string(), "true".to_string()),
+            ])),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        MarkdownRenderer::new().render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        assert!(generated
+            .contains("at [path/to/file1.ext (line 123)](path/to/file1.ext) by Alice on 2023-11-14 _(stale)_\n"));
+    }
+
+    #[test]
+    fn render_owners() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([(attributes::OUTPUT_FILE_NAME.to_string(), "test".to_string())]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("Billing").unwrap(),
+            Note::new(
+                FileLocation::new_relative("billing/invoice.rs", 1),
+                vec![NoteSpan::Text("note 1".to_string())],
+            ),
+        );
+
+        tree.merge_attributes(
+            &Handle::from_str("Billing").unwrap(),
+            HashMap::from([(
+                attributes::OWNERS.to_string(),
+                "@team-billing, @alice".to_string(),
+            )]),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        MarkdownRenderer::new().render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        assert!(generated.contains("Billing\n\n_Owners:_ @team-billing, @alice\n\n> note 1"));
+    }
+
+    #[test]
+    fn render_numbered_sections() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::OUTPUT_FILE_NAME.to_string(), "test".to_string()),
+                (attributes::TOC.to_string(), "true".to_string()),
+                (attributes::ANCHORS.to_string(), "github".to_string()),
+            ]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("A/B").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("see ".to_string()),
+                    NoteSpan::Link(Handle::from_str("A").unwrap()),
+                ],
+            ),
+        );
+
+        NumberingDecorator::new(1).decorate(&mut tree).unwrap();
+
+        let mut fs = StagingArea::new().unwrap();
+        MarkdownRenderer::new().render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        assert!(generated.contains("- [1 A](#1-a)\n\t- [1.1 B](#11-b)\n"));
+        assert!(generated.contains("\n## 1 A\n"));
+        assert!(generated.contains("\n### 1.1 B\n\n> see [1 A](#1-a)"));
+    }
 }
@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::model::handle::Handle;
+use crate::model::tree::Node;
+use crate::renderer::anchors::{AnchorStyle, Anchors};
+use crate::renderer::markdown::RendererSession;
+use crate::renderer::staging::StagingArea;
+use crate::renderer::{resolve_numbered_title, Renderer};
+
+const SUMMARY_FILE_NAME: &str = "SUMMARY.md";
+const ROOT_CHAPTER_FILE_NAME: &str = "README.md";
+
+/*@[Core/Renderer/mdBook]:
+mdBook renderer writes the `src` directory of an mdBook project: `SUMMARY.md` mirroring the tree and
+a chapter file per node. With `depth` set, only the nodes down to that depth get their own chapters,
+deeper nodes are rendered as sections of their closest chapter.
+
+The chapters are rendered by the Markdown renderer session, so the layout stays the same, except for
+the anchors, which are always derived from the handles (mdBook has its own heading ids rules). The root
+node becomes the prefix chapter (`README.md`). `book.toml` is never written, as it's usually maintained
+by hand, and mdBook can build the book without it anyway.
+*/
+pub struct MdBookRenderer {
+    path: PathBuf,
+    depth: Option<usize>,
+}
+
+impl MdBookRenderer {
+    pub fn new<P: Into<PathBuf>>(path: P, depth: Option<usize>) -> MdBookRenderer {
+        MdBookRenderer {
+            path: path.into(),
+            depth,
+        }
+    }
+
+    fn is_chapter(&self, node: &Node) -> bool {
+        node.handle().parts().len() <= self.depth.unwrap_or(usize::MAX)
+    }
+
+    fn collect_chapters<'a>(&self, node: &'a Node, chapters: &mut Vec<&'a Node>) {
+        if !self.is_chapter(node) {
+            return;
+        }
+
+        chapters.push(node);
+
+        for n in node.sorted_children() {
+            self.collect_chapters(n, chapters);
+        }
+    }
+
+    /*@[Core/Renderer/mdBook]:
+    Chapter file names are derived from the handles, which are not unique once slugified (`C#` and `C`
+    are both `c`), so the collisions are resolved with the same suffixes as the anchors
+    (see @[Core/Renderer/Anchors]). The names of the special files are reserved up front, as they would
+    clash on case-insensitive file systems.
+    */
+    fn chapter_file_names(chapters: &[&Node]) -> HashMap<Handle, String> {
+        let mut used = HashSet::from([
+            ROOT_CHAPTER_FILE_NAME
+                .trim_end_matches(".md")
+                .to_lowercase(),
+            SUMMARY_FILE_NAME.trim_end_matches(".md").to_lowercase(),
+        ]);
+
+        chapters
+            .iter()
+            .map(|n| {
+                let name = if n.handle().parts().is_empty() {
+                    ROOT_CHAPTER_FILE_NAME.to_string()
+                } else {
+                    let slug = Anchors::reserve(n.handle().as_url_safe_string(), &mut used);
+                    format!("{}.md", slug)
+                };
+                (n.handle().clone(), name)
+            })
+            .collect()
+    }
+
+    fn render_summary(
+        &self,
+        root: &Node,
+        chapters: &[&Node],
+        files: &HashMap<Handle, String>,
+        fs: &mut StagingArea,
+    ) -> Result<()> {
+        let mut summary = String::from("# Summary\n\n");
+
+        for n in chapters {
+            let depth = n.handle().parts().len();
+            let title = resolve_numbered_title(root, n.handle());
+            let file = &files[n.handle()];
+
+            if depth == 0 {
+                writeln!(summary, "[{}]({})\n", title, file)?;
+            } else {
+                writeln!(
+                    summary,
+                    "{}- [{}]({})",
+                    "    ".repeat(depth - 1),
+                    title,
+                    file
+                )?;
+            }
+        }
+
+        fs.open_as_new(self.path.join("src").join(SUMMARY_FILE_NAME))?
+            .write_str(&summary)?;
+
+        Ok(())
+    }
+}
+
+impl Renderer for MdBookRenderer {
+    fn render(&self, root: &Node, fs: &mut StagingArea) -> Result<()> {
+        let mut chapters = vec![];
+        self.collect_chapters(root, &mut chapters);
+
+        let files = Self::chapter_file_names(&chapters);
+
+        self.render_summary(root, &chapters, &files, fs)?;
+
+        let anchors = Anchors::build(root, AnchorStyle::Handle);
+
+        for n in &chapters {
+            let out = fs.open_as_new(self.path.join("src").join(&files[n.handle()]))?;
+            let mut session = RendererSession::new(root, out, &anchors, Some(&files));
+
+            session.render_node_body(1, n)?;
+
+            for c in n.sorted_children() {
+                if !self.is_chapter(c) {
+                    session.render_node(2, c)?;
+                }
+            }
+
+            if n.handle().parts().is_empty() {
+                session.render_footer()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::decorators::numbering::NumberingDecorator;
+    use crate::decorators::Decorator;
+    use crate::model::attributes;
+    use crate::model::file_location::FileLocation;
+    use crate::model::note::{Note, NoteSpan};
+
+    use super::*;
+
+    fn render_to_string(fs: &mut StagingArea, path: &str) -> String {
+        String::from_utf8(fs.open(path).unwrap().contents().unwrap())
+            .unwrap()
+            .replace(" \n", "\n")
+    }
+
+    #[test]
+    fn render_from_tree() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([
+                (attributes::TITLE.to_string(), "Big Nice Title".to_string()),
+                (attributes::APP_VERSION.to_string(), "0.1.0".to_string()),
+                (
+                    attributes::TIMESTAMP.to_string(),
+                    "2023-03-01 12:34:56".to_string(),
+                ),
+            ]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a/b/c").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("note 1 ".to_string()),
+                    NoteSpan::Link(Handle::from_str("x").unwrap()),
+                ],
+            ),
+        );
+
+        tree.add_note(
+            &Handle::from_str("x").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file2.ext", 234),
+                vec![NoteSpan::Text("note 2".to_string())],
+            ),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        let renderer = MdBookRenderer::new("book", Some(2));
+
+        renderer.render(&tree, &mut fs).unwrap();
+
+        assert_eq!(
+            r#"# Summary
+
+[Big Nice Title](README.md)
+
+- [a](a.md)
+    - [b](a+b.md)
+- [x](x.md)
+"#,
+            render_to_string(&mut fs, "book/src/SUMMARY.md")
+        );
+
+        assert_eq!(
+            r#"# <a id="a+b"></a> b
+
+## <a id="a+b+c"></a> c
+
+> note 1 [x](x.md#x)
+
+at [path/to/file1.ext (line 123)](path/to/file1.ext)
+
+
+
+"#,
+            render_to_string(&mut fs, "book/src/a+b.md")
+        );
+
+        assert_eq!(
+            r#"# <a id="x"></a> x
+
+> note 2
+
+at [path/to/file2.ext (line 234)](path/to/file2.ext)
+
+
+
+
+_Mentioned in:_
+- [c](a+b.md#a+b+c)
+"#,
+            render_to_string(&mut fs, "book/src/x.md")
+        );
+
+        assert!(render_to_string(&mut fs, "book/src/README.md")
+            .contains("# <a id=\"\"></a> Big Nice Title\n\n"));
+    }
+
+    #[test]
+    fn resolve_file_name_collisions() {
+        let mut tree = Node::root();
+
+        for h in ["C#", "C", "Readme"] {
+            tree.add_note(
+                &Handle::from_str(h).unwrap(),
+                Note::new(
+                    FileLocation::new_relative("path/to/file.ext", 1),
+                    vec![NoteSpan::Text(format!("note {}", h))],
+                ),
+            );
+        }
+
+        let mut fs = StagingArea::new().unwrap();
+        MdBookRenderer::new("book", None)
+            .render(&tree, &mut fs)
+            .unwrap();
+
+        let summary = render_to_string(&mut fs, "book/src/SUMMARY.md");
+        assert!(summary.contains("- [C](c.md)\n"));
+        assert!(summary.contains("- [C#](c-1.md)\n"));
+        assert!(summary.contains("- [Readme](readme-1.md)\n"));
+
+        assert!(render_to_string(&mut fs, "book/src/c.md").contains("> note C\n"));
+        assert!(render_to_string(&mut fs, "book/src/c-1.md").contains("> note C#\n"));
+    }
+
+    #[test]
+    fn number_summary_entries() {
+        let mut tree = Node::root();
+
+        for h in ["Billing", "Billing/Invoices", "Shipping"] {
+            tree.add_note(
+                &Handle::from_str(h).unwrap(),
+                Note::new(
+                    FileLocation::new_relative("path/to/file.ext", 1),
+                    vec![NoteSpan::Text(format!("note {}", h))],
+                ),
+            );
+        }
+
+        NumberingDecorator::new(1).decorate(&mut tree).unwrap();
+
+        let mut fs = StagingArea::new().unwrap();
+        MdBookRenderer::new("book", None)
+            .render(&tree, &mut fs)
+            .unwrap();
+
+        let summary = render_to_string(&mut fs, "book/src/SUMMARY.md");
+        assert!(summary.contains("- [1 Billing](billing.md)\n"));
+        assert!(summary.contains("    - [1.1 Invoices](billing+invoices.md)\n"));
+        assert!(summary.contains("- [2 Shipping](shipping.md)\n"));
+    }
+}
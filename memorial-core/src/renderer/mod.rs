@@ -1,11 +1,48 @@
 use anyhow::Result;
 
+use crate::model::attributes;
+use crate::model::handle::Handle;
 use crate::model::tree::Node;
 use crate::renderer::staging::StagingArea;
 
+pub mod anchors;
+pub mod graph;
+pub mod json;
 pub mod markdown;
+pub mod mdbook;
 pub mod staging;
+pub mod template;
 
 pub trait Renderer {
     fn render(&self, root: &Node, fs: &mut StagingArea) -> Result<()>;
 }
+
+pub(crate) fn resolve_node_title(root: &Node, handle: &Handle) -> String {
+    root.find_node(handle)
+        .and_then(|n| n.attributes().get(attributes::TITLE))
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| {
+            handle
+                .parts()
+                .last()
+                .cloned()
+                .unwrap_or_else(|| String::from("(root)"))
+        })
+}
+
+/*@[Core/Renderer]:
+Section numbers (see @[Core/Decorators/Numbering]) are part of the titles wherever the titles
+are used for navigation: headings, table of contents and links. This also keeps GitHub-style anchors
+matching the headings.
+*/
+pub(crate) fn resolve_numbered_title(root: &Node, handle: &Handle) -> String {
+    let title = resolve_node_title(root, handle);
+
+    match root
+        .find_node(handle)
+        .and_then(|n| n.attributes().get(attributes::NUMBER))
+    {
+        Some(number) => format!("{} {}", number, title),
+        None => title,
+    }
+}
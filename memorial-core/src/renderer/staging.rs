@@ -1,9 +1,11 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Write;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use tempfile::TempDir;
 
 /*@[Core/Renderer/Staging]:
 `StagingArea` acts as an intermediate temp file system for keeping the rendered files until the rendering
@@ -15,90 +17,556 @@ restoring the original state, but this is an unnecessary limitation. Also it's s
 which can be avoided.
 */
 pub struct StagingArea {
-    staged: HashMap<PathBuf, StagedFile>,
+    dir: Option<TempDir>,
+    staged: BTreeMap<PathBuf, StagedFile>,
+    counter: usize,
+    active: Option<PathBuf>,
 }
 
+/*@[Core/Renderer/Staging]:
+The staged files are kept in a temp directory, so the size of the output is not limited by memory.
+When created for the output root (`in_dir`), the directory is placed in the closest existing ancestor of the root,
+so it is on the same file system as the final files and committing a file is an atomic rename. The root itself
+is not created until the flush, so a failed rendering leaves nothing behind. Otherwise (or for the absolute
+paths pointing elsewhere) the files are copied. The directory is removed when the staging area is dropped,
+whatever the outcome.
+*/
 impl StagingArea {
-    pub fn new() -> StagingArea {
-        StagingArea {
+    pub fn new() -> Result<StagingArea> {
+        Self::with_dir(tempfile::Builder::new().prefix("memorial-").tempdir()?)
+    }
+
+    pub fn in_dir<P: AsRef<Path>>(root: P) -> Result<StagingArea> {
+        let base = root
+            .as_ref()
+            .ancestors()
+            .find(|p| p.is_dir())
+            .unwrap_or_else(|| Path::new("."));
+
+        Self::with_dir(
+            tempfile::Builder::new()
+                .prefix(".memorial-staging-")
+                .tempdir_in(base)?,
+        )
+    }
+
+    fn with_dir(dir: TempDir) -> Result<StagingArea> {
+        fs::create_dir(dir.path().join(FILES_DIR))?;
+        fs::create_dir(dir.path().join(BACKUP_DIR))?;
+
+        Ok(StagingArea {
+            dir: Some(dir),
             staged: Default::default(),
+            counter: 0,
+            active: None,
+        })
+    }
+
+    fn dir(&self) -> Result<&Path> {
+        self.dir
+            .as_ref()
+            .map(|d| d.path())
+            .ok_or_else(|| anyhow!("Staging area is no longer usable after a failed flush"))
+    }
+
+    pub fn open_as_new<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut StagedFile> {
+        let staged_path = self.dir()?.join(FILES_DIR).join(self.counter.to_string());
+        self.counter += 1;
+
+        let file = StagedFile::create(staged_path)?;
+
+        // replacing the file staged under the same path, if any
+        let previous = self.staged.insert(path.as_ref().to_path_buf(), file);
+        if let Some(p) = previous {
+            p.discard()?;
         }
+
+        self.activate(path.as_ref())?;
+        Ok(self.staged.get_mut(path.as_ref()).unwrap())
     }
 
-    pub fn open_as_new<P: AsRef<Path>>(&mut self, path: P) -> &mut StagedFile {
-        let p: PathBuf = path.as_ref().to_path_buf();
-        self.staged.insert(p.clone(), StagedFile::new());
-        self.staged.get_mut(&p).unwrap()
+    /*@[Core/Renderer/Staging]:
+    Renderers can stage any number of files (e.g. a chapter per node), so keeping them all open would hit
+    the limit of open files. Only one staged file can be borrowed at a time anyway, so the previously
+    borrowed one is closed whenever another one is handed out, and is reopened if written to again.
+    */
+    fn activate(&mut self, path: &Path) -> Result<()> {
+        if let Some(previous) = self.active.replace(path.to_path_buf()) {
+            if previous != path {
+                if let Some(f) = self.staged.get_mut(&previous) {
+                    f.release()?;
+                }
+            }
+        }
+
+        Ok(())
     }
 
+    /*@[Core/Renderer/Staging]:
+    Flushing is all-or-nothing: the existing files are moved aside as backups (into the staging directory)
+    before being replaced. If any of the files can't be written, the already written ones are removed,
+    the backups are restored, and the directories created along the way are removed (if left empty).
+
+    Rolling back can fail too. The errors are reported along with the original one, and if any of the backups
+    couldn't be restored, the staging directory is kept (instead of being removed with the original files
+    in it), so the files can be restored by hand.
+    */
     pub fn flush_to_os_fs<P: AsRef<Path>>(&mut self, root: P) -> Result<()> {
-        for (path, file) in self.staged.drain() {
+        let mut log = CommitLog::default();
+
+        let staged = std::mem::take(&mut self.staged);
+
+        for (i, (path, file)) in staged.into_iter().enumerate() {
             let full_path = if path.is_relative() {
                 root.as_ref().join(path)
             } else {
                 path
             };
 
-            fs::write(full_path, file.contents)?;
+            let backup = self.dir()?.join(BACKUP_DIR).join(i.to_string());
+
+            if let Err(e) = log.commit(file, &full_path, &backup) {
+                return Err(self.roll_back(log, e, &full_path));
+            }
         }
 
         Ok(())
     }
 
+    fn roll_back(&mut self, log: CommitLog, error: anyhow::Error, path: &Path) -> anyhow::Error {
+        let rollback = log.rollback();
+
+        if rollback.errors.is_empty() {
+            return error.context(format!(
+                "Failed to write {}, all changes are rolled back",
+                path.display()
+            ));
+        }
+
+        let mut message = format!(
+            "Failed to write {}, and rolling back failed too:\n{}",
+            path.display(),
+            rollback
+                .errors
+                .iter()
+                .map(|e| format!("- {:#}", e))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        if rollback.backups_lost {
+            // `TempDir::keep` is not available in all the supported versions of `tempfile`
+            #[allow(deprecated)]
+            if let Some(kept) = self.dir.take().map(|d| d.into_path()) {
+                message.push_str(&format!(
+                    "\nThe original files are kept in {}",
+                    kept.join(BACKUP_DIR).display()
+                ));
+            }
+        }
+
+        error.context(message)
+    }
+
     pub fn flush_to_stdout(&mut self) -> Result<()> {
-        for (path, file) in self.staged.drain() {
+        for (path, file) in std::mem::take(&mut self.staged) {
             println!(
                 "Staged file: {}\n{}\n\n",
                 &path.display(),
-                String::from_utf8(file.contents)?
+                String::from_utf8(file.into_contents()?)?
             )
         }
 
         Ok(())
     }
 
-    pub fn open<P: AsRef<Path>>(&self, path: P) -> Option<&StagedFile> {
-        self.staged.get(&path.as_ref().to_path_buf())
+    pub fn open<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut StagedFile> {
+        if !self.staged.contains_key(path.as_ref()) {
+            return None;
+        }
+
+        self.activate(path.as_ref()).ok()?;
+        self.staged.get_mut(path.as_ref())
     }
 }
 
-/*@[Core/Renderer/Staging]:
-Staging in the current implementation stores data in memory. This approach simplifies design but
-obviously won't scale well. For the future versions, some memory independent storage should be used.
-E.g. `/tmp`.
-*/
+const FILES_DIR: &str = "files";
+const BACKUP_DIR: &str = "backup";
+
+#[derive(Default)]
+struct CommitLog {
+    created_dirs: Vec<PathBuf>,
+    written: Vec<PathBuf>,
+    backups: Vec<(PathBuf, PathBuf)>,
+}
+
+impl CommitLog {
+    fn commit(&mut self, file: StagedFile, target: &Path, backup: &Path) -> Result<()> {
+        let staged = file.close()?;
+
+        if let Some(parent) = target.parent() {
+            self.create_dirs(parent)?;
+        }
+
+        if let Ok(metadata) = fs::symlink_metadata(target) {
+            // only files are replaced, anything else (like a directory) would be lost with the backup
+            if !metadata.is_file() && !metadata.file_type().is_symlink() {
+                return Err(anyhow!("{} exists and is not a file", target.display()));
+            }
+
+            move_file(target, backup)?;
+            self.backups
+                .push((backup.to_path_buf(), target.to_path_buf()));
+        }
+
+        move_file(&staged, target)?;
+        self.written.push(target.to_path_buf());
+
+        Ok(())
+    }
+
+    fn create_dirs(&mut self, dir: &Path) -> Result<()> {
+        let mut missing = vec![];
+        let mut current = Some(dir);
+
+        while let Some(d) = current {
+            if d.as_os_str().is_empty() || d.exists() {
+                break;
+            }
+            missing.push(d.to_path_buf());
+            current = d.parent();
+        }
+
+        for d in missing.into_iter().rev() {
+            fs::create_dir(&d)?;
+            self.created_dirs.push(d);
+        }
+
+        Ok(())
+    }
+
+    fn rollback(self) -> Rollback {
+        let mut result = Rollback::default();
+
+        for p in self.written.iter().rev() {
+            if let Err(e) = fs::remove_file(p) {
+                result
+                    .errors
+                    .push(anyhow!("Failed to remove {}: {}", p.display(), e));
+            }
+        }
+
+        for (backup, target) in self.backups.iter().rev() {
+            if let Err(e) = move_file(backup, target) {
+                result.errors.push(e.context(format!(
+                    "Failed to restore {} from {}",
+                    target.display(),
+                    backup.display()
+                )));
+                result.backups_lost = true;
+            }
+        }
+
+        for d in self.created_dirs.iter().rev() {
+            if let Err(e) = fs::remove_dir(d) {
+                result
+                    .errors
+                    .push(anyhow!("Failed to remove {}: {}", d.display(), e));
+            }
+        }
+
+        result
+    }
+}
+
+#[derive(Default)]
+struct Rollback {
+    errors: Vec<anyhow::Error>,
+    backups_lost: bool,
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Ok(_) => return Ok(()),
+        Err(e) if !is_cross_device(&e) => {
+            return Err(anyhow!(
+                "Failed to move {} to {}: {}",
+                from.display(),
+                to.display(),
+                e
+            ))
+        }
+        _ => {}
+    }
+
+    fs::copy(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+    fs::remove_file(from)?;
+
+    Ok(())
+}
+
+// `ErrorKind::CrossesDevices` is not available on the supported Rust version
+fn is_cross_device(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const CROSS_DEVICE: i32 = 18; // EXDEV
+    #[cfg(windows)]
+    const CROSS_DEVICE: i32 = 17; // ERROR_NOT_SAME_DEVICE
+    #[cfg(not(any(unix, windows)))]
+    const CROSS_DEVICE: i32 = -1;
+
+    e.raw_os_error() == Some(CROSS_DEVICE)
+}
+
 pub struct StagedFile {
-    contents: Vec<u8>,
+    path: PathBuf,
+    out: Option<BufWriter<File>>,
 }
 
 impl StagedFile {
-    pub fn new() -> StagedFile {
-        StagedFile {
-            contents: Default::default(),
+    fn create(path: PathBuf) -> Result<StagedFile> {
+        File::create(&path)?;
+        Ok(StagedFile { path, out: None })
+    }
+
+    fn writer(&mut self) -> std::io::Result<&mut BufWriter<File>> {
+        if self.out.is_none() {
+            let file = OpenOptions::new().append(true).open(&self.path)?;
+            self.out = Some(BufWriter::new(file));
+        }
+
+        Ok(self.out.as_mut().unwrap())
+    }
+
+    // closes the file, keeping the writer if the buffered data can't be written
+    fn release(&mut self) -> Result<()> {
+        if let Some(out) = self.out.take() {
+            if let Err(e) = out.into_inner() {
+                let error = anyhow!("Failed to write {}: {}", self.path.display(), e.error());
+                self.out = Some(e.into_inner());
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn contents(&mut self) -> Result<Vec<u8>> {
+        if let Some(out) = &mut self.out {
+            out.flush()?;
         }
+        Ok(fs::read(&self.path)?)
     }
 
-    pub fn contents(&self) -> &Vec<u8> {
-        &self.contents
+    fn into_contents(self) -> Result<Vec<u8>> {
+        let path = self.close()?;
+        Ok(fs::read(path)?)
+    }
+
+    fn close(mut self) -> Result<PathBuf> {
+        self.release()?;
+        OpenOptions::new()
+            .write(true)
+            .open(&self.path)?
+            .sync_all()?;
+        Ok(self.path)
+    }
+
+    fn discard(self) -> Result<()> {
+        let path = self.close()?;
+        fs::remove_file(path)?;
+        Ok(())
     }
 }
 
 impl Write for StagedFile {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.contents.write(buf)
+        self.writer()?.write(buf)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        //noop
-        Ok(())
+        match &mut self.out {
+            Some(out) => out.flush(),
+            None => Ok(()),
+        }
     }
 }
 
 impl std::fmt::Write for StagedFile {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
-        match self.write(s.as_bytes()) {
+        match self.write_all(s.as_bytes()) {
             Err(_) => Err(std::fmt::Error),
             _ => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn flush_all_files() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.md"), "old").unwrap();
+
+        let mut fs = StagingArea::in_dir(root.path()).unwrap();
+        fs.open_as_new("a.md").unwrap().write_str("new a").unwrap();
+        fs.open_as_new("book/src/b.md")
+            .unwrap()
+            .write_str("new b")
+            .unwrap();
+
+        assert_eq!(
+            b"new a".to_vec(),
+            fs.open("a.md").unwrap().contents().unwrap()
+        );
+
+        fs.flush_to_os_fs(root.path()).unwrap();
+        drop(fs);
+
+        assert_eq!(
+            "new a",
+            fs::read_to_string(root.path().join("a.md")).unwrap()
+        );
+        assert_eq!(
+            "new b",
+            fs::read_to_string(root.path().join("book/src/b.md")).unwrap()
+        );
+        assert_eq!(2, fs::read_dir(root.path()).unwrap().count());
+    }
+
+    #[test]
+    fn roll_back_on_failure() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.md"), "old").unwrap();
+        fs::write(root.path().join("c"), "not a directory").unwrap();
+
+        let mut fs = StagingArea::in_dir(root.path()).unwrap();
+        fs.open_as_new("a.md").unwrap().write_str("new a").unwrap();
+        fs.open_as_new("b/new.md")
+            .unwrap()
+            .write_str("new b")
+            .unwrap();
+        fs.open_as_new("c/new.md")
+            .unwrap()
+            .write_str("new c")
+            .unwrap();
+
+        assert!(fs.flush_to_os_fs(root.path()).is_err());
+        drop(fs);
+
+        assert_eq!("old", fs::read_to_string(root.path().join("a.md")).unwrap());
+        assert!(!root.path().join("b").exists());
+        assert_eq!(2, fs::read_dir(root.path()).unwrap().count());
+    }
+
+    #[test]
+    fn keep_directories() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("docs")).unwrap();
+        fs::write(root.path().join("docs/precious.md"), "precious").unwrap();
+
+        let mut fs = StagingArea::in_dir(root.path()).unwrap();
+        fs.open_as_new("a.md").unwrap().write_str("new a").unwrap();
+        fs.open_as_new("docs")
+            .unwrap()
+            .write_str("new docs")
+            .unwrap();
+
+        assert!(fs.flush_to_os_fs(root.path()).is_err());
+        drop(fs);
+
+        assert_eq!(
+            "precious",
+            fs::read_to_string(root.path().join("docs/precious.md")).unwrap()
+        );
+        assert!(!root.path().join("a.md").exists());
+    }
+
+    #[test]
+    fn create_root_on_flush() {
+        let root = tempfile::tempdir().unwrap();
+        let output = root.path().join("out/docs");
+
+        let mut fs = StagingArea::in_dir(&output).unwrap();
+        fs.open_as_new("a.md").unwrap().write_str("new a").unwrap();
+        assert!(!output.exists());
+
+        fs.flush_to_os_fs(&output).unwrap();
+        drop(fs);
+
+        assert_eq!("new a", fs::read_to_string(output.join("a.md")).unwrap());
+        assert_eq!(1, fs::read_dir(root.path()).unwrap().count());
+    }
+
+    #[test]
+    fn keep_backups_on_failed_rollback() {
+        let root = tempfile::tempdir().unwrap();
+
+        let mut fs = StagingArea::in_dir(root.path()).unwrap();
+        let backup = fs.dir().unwrap().join(BACKUP_DIR).join("0");
+        fs::write(&backup, "old").unwrap();
+
+        // the target's directory is gone, so the backup can't be moved back
+        let log = CommitLog {
+            backups: vec![(backup.clone(), root.path().join("gone/a.md"))],
+            ..Default::default()
+        };
+
+        let error = fs.roll_back(log, anyhow!("disk full"), Path::new("b.md"));
+        drop(fs);
+
+        let message = format!("{:#}", error);
+        assert!(message.contains("rolling back failed"));
+        assert!(message.contains("The original files are kept in"));
+        assert_eq!("old", fs::read_to_string(&backup).unwrap());
+
+        fs::remove_dir_all(backup.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stage_more_files_than_open_files_limit() {
+        struct Limit(libc::rlimit);
+
+        impl Drop for Limit {
+            fn drop(&mut self) {
+                unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &self.0) };
+            }
+        }
+
+        let mut original = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        assert_eq!(0, unsafe {
+            libc::getrlimit(libc::RLIMIT_NOFILE, &mut original)
+        });
+
+        let root = tempfile::tempdir().unwrap();
+        let mut fs = StagingArea::in_dir(root.path()).unwrap();
+
+        let low = libc::rlimit {
+            rlim_cur: original.rlim_cur.min(128),
+            rlim_max: original.rlim_max,
+        };
+        let _restore = Limit(original);
+        assert_eq!(0, unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &low) });
+
+        for i in 0..300 {
+            let out = fs.open_as_new(format!("book/{}.md", i)).unwrap();
+            write!(out, "chapter {}", i).unwrap();
+        }
+
+        fs.flush_to_os_fs(root.path()).unwrap();
+
+        assert_eq!(300, fs::read_dir(root.path().join("book")).unwrap().count());
+        assert_eq!(
+            "chapter 299",
+            fs::read_to_string(root.path().join("book/299.md")).unwrap()
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use anyhow::{Context as _, Result};
+use serde_derive::Serialize;
+use tera::{Context, Tera, Value};
+
+use crate::model::file_location::FileLocation;
+use crate::model::handle::Handle;
+use crate::model::note::{Note, NoteSpan};
+use crate::model::tree::Node;
+use crate::renderer::anchors::{AnchorStyle, Anchors};
+use crate::renderer::staging::StagingArea;
+use crate::renderer::{resolve_numbered_title, Renderer};
+
+const TEMPLATE_NAME: &str = "main";
+
+/*@[Core/Renderer/Template]:
+Template renderer is an escape hatch for layouts not covered by the built-in renderers. The templates
+are processed by Tera (a Jinja2-like engine) and can produce any text format.
+
+Instead of exposing the model types directly, the renderer prepares a view model with everything a
+template might need already resolved (titles, anchors, links), so the templates can stay simple and
+the model types don't have to be serializable.
+*/
+pub struct TemplateRenderer {
+    tera: Tera,
+    path: String,
+}
+
+impl TemplateRenderer {
+    pub fn new(template: &str, path: String) -> Result<TemplateRenderer> {
+        let mut tera = Tera::default();
+        tera.add_raw_template(TEMPLATE_NAME, template)
+            .context("Unable to parse the template")?;
+
+        Ok(TemplateRenderer { tera, path })
+    }
+}
+
+impl Renderer for TemplateRenderer {
+    fn render(&self, root: &Node, fs: &mut StagingArea) -> Result<()> {
+        let ctx = ViewContext {
+            root,
+            anchors: Anchors::build(root, AnchorStyle::Handle),
+        };
+
+        let mut links = HashMap::new();
+        collect_links(&ctx, root, &mut links);
+
+        let mut tera = self.tera.clone();
+        tera.register_function("link", LinkFunction::new(links.clone(), LinkPart::Markdown));
+        tera.register_function("title", LinkFunction::new(links.clone(), LinkPart::Title));
+        tera.register_function("anchor", LinkFunction::new(links, LinkPart::Anchor));
+
+        let mut context = Context::new();
+        context.insert("root", &ViewNode::from_node(&ctx, root, 0));
+
+        let rendered = tera
+            .render(TEMPLATE_NAME, &context)
+            .context("Unable to render the template")?;
+
+        fs.open_as_new(&self.path)?.write_all(rendered.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+struct ViewContext<'a> {
+    root: &'a Node,
+    anchors: Anchors,
+}
+
+#[derive(Serialize, Clone)]
+struct ViewLink {
+    handle: String,
+    title: String,
+    anchor: String,
+}
+
+#[derive(Serialize)]
+struct ViewNode {
+    handle: String,
+    parts: Vec<String>,
+    title: String,
+    anchor: String,
+    level: usize,
+    attributes: BTreeMap<String, String>,
+    notes: Vec<ViewNote>,
+    extra: Vec<ViewLocation>,
+    mentions: Vec<ViewLink>,
+    children: Vec<ViewNode>,
+}
+
+#[derive(Serialize)]
+struct ViewNote {
+    location: ViewLocation,
+    attributes: BTreeMap<String, String>,
+    spans: Vec<ViewSpan>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ViewSpan {
+    Text { text: String },
+    Link { link: ViewLink },
+}
+
+#[derive(Serialize)]
+struct ViewLocation {
+    title: String,
+    path: String,
+    line: usize,
+}
+
+impl ViewLink {
+    fn from_handle(ctx: &ViewContext, handle: &Handle) -> ViewLink {
+        ViewLink {
+            handle: handle.parts().join("/"),
+            title: resolve_numbered_title(ctx.root, handle),
+            anchor: ctx.anchors.get(handle),
+        }
+    }
+}
+
+impl ViewNode {
+    fn from_node(ctx: &ViewContext, node: &Node, level: usize) -> ViewNode {
+        let link = ViewLink::from_handle(ctx, node.handle());
+
+        let mut mentions: Vec<ViewLink> = node
+            .mentions()
+            .iter()
+            .map(|h| ViewLink::from_handle(ctx, h))
+            .collect();
+        mentions.sort_by(|a, b| a.handle.cmp(&b.handle));
+
+        ViewNode {
+            handle: link.handle,
+            parts: node.handle().parts().clone(),
+            title: link.title,
+            anchor: link.anchor,
+            level,
+            attributes: node
+                .attributes()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            notes: node
+                .notes()
+                .iter()
+                .map(|n| ViewNote::from_note(ctx, n))
+                .collect(),
+            extra: node
+                .extra()
+                .iter()
+                .map(ViewLocation::from_location)
+                .collect(),
+            mentions,
+            children: node
+                .sorted_children()
+                .into_iter()
+                .map(|n| ViewNode::from_node(ctx, n, level + 1))
+                .collect(),
+        }
+    }
+}
+
+impl ViewNote {
+    fn from_note(ctx: &ViewContext, note: &Note) -> ViewNote {
+        ViewNote {
+            location: ViewLocation::from_location(note.location()),
+            attributes: note
+                .attributes()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            spans: note
+                .spans()
+                .iter()
+                .map(|s| match s {
+                    NoteSpan::Text(text) => ViewSpan::Text { text: text.clone() },
+                    NoteSpan::Link(handle) => ViewSpan::Link {
+                        link: ViewLink::from_handle(ctx, handle),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ViewLocation {
+    fn from_location(location: &FileLocation) -> ViewLocation {
+        ViewLocation {
+            title: location.title().to_string(),
+            path: location.path().to_string(),
+            line: location.line(),
+        }
+    }
+}
+
+fn collect_links(ctx: &ViewContext, node: &Node, links: &mut HashMap<String, ViewLink>) {
+    let link = ViewLink::from_handle(ctx, node.handle());
+    links.insert(link.handle.clone(), link);
+
+    for n in node.children().values() {
+        collect_links(ctx, n, links);
+    }
+}
+
+enum LinkPart {
+    Markdown,
+    Title,
+    Anchor,
+}
+
+/*@[Core/Renderer/Template]:
+Helper functions `link`, `title` and `anchor` accept a `handle` argument, written the same way as
+in the notes (e.g. `link(handle="Domain/Aggregate")`). This allows referencing arbitrary nodes from
+the templates without walking the tree.
+*/
+struct LinkFunction {
+    links: HashMap<String, ViewLink>,
+    part: LinkPart,
+}
+
+impl LinkFunction {
+    fn new(links: HashMap<String, ViewLink>, part: LinkPart) -> LinkFunction {
+        LinkFunction { links, part }
+    }
+}
+
+impl tera::Function for LinkFunction {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let raw = args
+            .get("handle")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("Missing `handle` argument"))?;
+
+        let handle = Handle::from_str(raw).map_err(|e| tera::Error::msg(e.to_string()))?;
+
+        let link = self
+            .links
+            .get(&handle.parts().join("/"))
+            .ok_or_else(|| tera::Error::msg(format!("Unknown handle `{}`", raw)))?;
+
+        Ok(Value::String(match self.part {
+            LinkPart::Markdown => format!("[{}](#{})", link.title, link.anchor),
+            LinkPart::Title => link.title.clone(),
+            LinkPart::Anchor => link.anchor.clone(),
+        }))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::attributes;
+
+    use super::*;
+
+    #[test]
+    fn render_from_tree() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::ROOT,
+            HashMap::from([(attributes::TITLE.to_string(), "Big Nice Title".to_string())]),
+        );
+
+        tree.add_note(
+            &Handle::from_str("a/b").unwrap(),
+            Note::new(
+                FileLocation::new_relative("path/to/file1.ext", 123),
+                vec![
+                    NoteSpan::Text("note 1".to_string()),
+                    NoteSpan::Link(Handle::from_str("a/c").unwrap()),
+                ],
+            )
+            .with_attributes(HashMap::from([(
+                attributes::DEPRECATED.to_string(),
+                "".to_string(),
+            )])),
+        );
+
+        tree.merge_attributes(
+            &Handle::from_str("a/c").unwrap(),
+            HashMap::from([(attributes::TITLE.to_string(), "Sub title".to_string())]),
+        );
+
+        let template = r#"
+{%- macro node(n) -%}
+{{ n.level }}. {{ n.title }} ({{ n.anchor }})
+{% for note in n.notes -%}
+{% if note.attributes.deprecated is defined %}[deprecated] {% endif %}{% for s in note.spans %}{% if s.type == "link" %}<{{ s.link.title }}>{% else %}{{ s.text }}{% endif %}{% endfor %} @ {{ note.location.path }}:{{ note.location.line }}
+{% endfor -%}
+{% for m in n.mentions %}mentioned in {{ m.title }}
+{% endfor -%}
+{% for c in n.children %}{{ self::node(n=c) }}{% endfor -%}
+{%- endmacro node -%}
+{{ self::node(n=root) }}
+See {{ link(handle="a / c") }}, titled "{{ title(handle="a/c") }}" at #{{ anchor(handle="a/c") }}.
+"#;
+
+        let mut fs = StagingArea::new().unwrap();
+        let renderer = TemplateRenderer::new(template, "test".to_string()).unwrap();
+
+        renderer.render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+
+        let expected = r#"0. Big Nice Title ()
+1. a (a)
+2. b (a+b)
+[deprecated] note 1<Sub title> @ path/to/file1.ext:123
+2. Sub title (a+c)
+mentioned in b
+
+See [Sub title](#a+c), titled "Sub title" at #a+c.
+"#;
+
+        assert_eq!(expected, generated);
+    }
+
+    #[test]
+    fn render_numbered_titles() {
+        let mut tree = Node::root();
+
+        tree.merge_attributes(
+            &Handle::from_str("a/c").unwrap(),
+            HashMap::from([(attributes::NUMBER.to_string(), "1.2".to_string())]),
+        );
+
+        let mut fs = StagingArea::new().unwrap();
+        let renderer = TemplateRenderer::new(
+            r#"{% for c in root.children %}{{ c.children[0].title }}{% endfor %}, {{ link(handle="a/c") }}"#,
+            "test".to_string(),
+        )
+        .unwrap();
+
+        renderer.render(&tree, &mut fs).unwrap();
+
+        let generated = String::from_utf8(fs.open("test").unwrap().contents().unwrap()).unwrap();
+        assert_eq!("1.2 c, [1.2 c](#a+c)", generated);
+    }
+
+    #[test]
+    fn fail_on_unknown_handles() {
+        let tree = Node::root();
+
+        let mut fs = StagingArea::new().unwrap();
+        let renderer =
+            TemplateRenderer::new(r#"{{ link(handle="x/y") }}"#, "test".to_string()).unwrap();
+
+        assert!(renderer.render(&tree, &mut fs).is_err());
+    }
+}
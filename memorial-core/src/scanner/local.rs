@@ -32,8 +32,14 @@ impl LocalFileScanner {
     }
 
     fn visit(&self, path: &Path, target: &Sender<LocalFile>) -> Result<()> {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
+        /*@[Core/Scanner]:
+        The order of directory entries returned by the OS is file system dependent. Entries are sorted by
+        name to make the order of the scanned files (and so the order of the collected notes) stable.
+        */
+        let mut entries = fs::read_dir(path)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
             let path = entry.path();
             let local_path = path.strip_prefix(&self.root)?;
 
@@ -83,7 +89,6 @@ impl File for LocalFile {
 
 #[cfg(test)]
 mod test {
-    use std::collections::HashSet;
     use std::env;
     use std::sync::mpsc;
     use std::sync::mpsc::Receiver;
@@ -105,20 +110,15 @@ mod test {
 
         let valid_files: Vec<_> = rx.into_iter().collect();
 
-        assert_eq!(2, valid_files.len());
         assert_eq!(
-            HashSet::from_iter(vec!(
-                PathBuf::from_iter(["src", "tests", "cases", "go", "app.go"])
-                    .to_str()
-                    .unwrap(),
-                PathBuf::from_iter(["src", "tests", "cases", "go", "domain.go"])
-                    .to_str()
-                    .unwrap(),
-            )),
+            vec!(
+                PathBuf::from_iter(["src", "tests", "cases", "go", "app.go"]),
+                PathBuf::from_iter(["src", "tests", "cases", "go", "domain.go"]),
+            ),
             valid_files
                 .iter()
-                .map(|f| f.local_path.to_str().unwrap())
-                .collect::<HashSet<_>>(),
+                .map(|f| f.local_path.clone())
+                .collect::<Vec<_>>(),
         );
     }
 }
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use std::sync::mpsc::Sender;
 
 pub mod local;
-mod path_filter;
+pub(crate) mod path_filter;
 
 pub trait File {
     fn path(&self) -> &PathBuf;
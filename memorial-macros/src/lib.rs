@@ -14,6 +14,7 @@ pub fn derive_file_parser(input: TokenStream) -> TokenStream {
             fn rule_to_quote(pair: Pair<Rule>) -> Quote {
                 let line = pair.line_col().0;
                 let indent = pair.line_col().1 - 1;
+                let lines = pair.as_str().trim_end().matches('\n').count() + 1;
 
                 /*@[Core/Parser]:
                 Handling of the indentations should be ideally done within the generated parser.
@@ -29,6 +30,7 @@ pub fn derive_file_parser(input: TokenStream) -> TokenStream {
 
                 Quote {
                     line,
+                    lines,
                     body,
                 }
             }